@@ -1,92 +1,499 @@
-use std::io::{BufRead, BufReader, Error};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Error, Read, Write};
+use std::path::Path;
 use std::process::ChildStderr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 use std::{
     process::{Child, ChildStdout, Command, Stdio},
     str,
-    sync::mpsc::{self, Receiver, Sender},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
     thread,
 };
 
 use log::{debug, trace};
 use regex::Regex;
 
-use crate::structs::{KubeEnv, KubeEnvData, TUIError, DEV, PROD};
+use crate::config::data_dir;
+use crate::connectivity::{diagnose, Timeouts};
+use crate::errors::{ErrorReport, Severity};
+use crate::kubeconfig::kubeconfig;
+use crate::log_merge::{merge_logs, reorder_window, strip_timestamp, timestamps_enabled};
+use crate::log_reconnect::{auth_expired, Backoff, StreamResume};
+use crate::log_sources::{log_sources, LogSource};
+use crate::namespaces::default_namespace;
+use crate::pod_watch::{pod_marker, PodChange, PodWatch};
+use crate::processes::{kill_grace, processes, run_for_action, terminate, SpawnRegistered};
+use crate::scheduler::RefreshScheduler;
+use crate::structs::{KubeEnv, KubeEnvData, TUIError};
+use crate::timestamp::now_secs;
+use crate::widget_state::LogExport;
 use crate::{TUIAction, TUIEvent};
 
-pub fn start(event_tx: Sender<TUIEvent>, action_rx: Receiver<TUIAction>) {
-    while let Ok(action) = action_rx.recv() {
-        debug!("handling action: {:?}", action);
-        match action {
-            TUIAction::ChangeEnv(env) => {
-                let env_data = match env {
-                    KubeEnv::Dev => DEV,
-                    KubeEnv::Prod => PROD,
-                };
-                match check_connectivity(&event_tx) {
-                    Ok(_) => match update_kubeconfig(env_data, &event_tx) {
-                        Ok(_) => {
-                            event_tx.send(TUIEvent::IsConnected).unwrap();
-                            event_tx.send(TUIEvent::ClearError).unwrap();
+/// State the action thread keeps between actions.
+struct ActionContext {
+    /// Environment of the main pane.
+    kube_env: KubeEnv,
+    /// Namespace picked per environment.
+    namespaces: HashMap<KubeEnv, String>,
+    /// Stop flags of the running streams, by name.
+    streams: HashMap<String, Arc<AtomicBool>>,
+    /// Where the log streams send their events, merged in time order when timestamps are on.
+    logs_tx: Sender<TUIEvent>,
+    /// Set when a log stream stopped because the login expired, see `StreamFlags`.
+    logs_need_login: Arc<AtomicBool>,
+    /// Sends the actions following up on the ones in flight.
+    action_tx: Sender<TUIAction>,
+    /// Actions running on threads of their own, by id.
+    in_flight: Arc<Mutex<HashMap<u64, InFlight>>>,
+    next_action: u64,
+}
+
+/// An action running on a thread of its own.
+struct InFlight {
+    action: TUIAction,
+    /// Environment the action runs against.
+    env: KubeEnv,
+    /// Drops the events of the action once set.
+    cancel: Arc<AtomicBool>,
+}
+
+impl InFlight {
+    /// Whether the action only fetches what the main pane shows of `env`, which is of no use
+    /// anymore once the main pane moved to another one. Changes, exports and the fetches of the
+    /// split pane are left to finish.
+    fn fetches_for(&self, env: KubeEnv) -> bool {
+        self.env == env
+            && matches!(
+                self.action,
+                TUIAction::ChangeEnv(_)
+                    | TUIAction::CheckConnectivity
+                    | TUIAction::GetPods
+                    | TUIAction::DescribePod(_)
+                    | TUIAction::GetDeployments
+                    | TUIAction::GetTop
+                    | TUIAction::GetNamespaces
+            )
+    }
+}
+
+impl ActionContext {
+    fn namespace(&self, env: KubeEnv) -> String {
+        self.namespaces
+            .get(&env)
+            .cloned()
+            .unwrap_or_else(default_namespace)
+    }
+
+    /// Stops the stream registered under `name`, if it is running.
+    fn stop_stream(&mut self, name: &str) {
+        if let Some(stop) = self.streams.remove(name) {
+            stop.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Stops the stream registered under `name` and returns the stop flag for its replacement.
+    fn replace_stream(&mut self, name: &str) -> Arc<AtomicBool> {
+        self.stop_stream(name);
+        let stop = Arc::new(AtomicBool::new(false));
+        self.streams.insert(name.to_string(), stop.clone());
+        stop
+    }
+
+    /// Runs `work` for `action` on a thread of its own, unless the same action is still in
+    /// flight, so a hanging command holds up nothing else. `work` gets the label of the action, to
+    /// report its errors with.
+    fn run_in_flight(
+        &mut self,
+        action: &TUIAction,
+        event_tx: &Sender<TUIEvent>,
+        work: impl FnOnce(&Sender<TUIEvent>, &str) + Send + 'static,
+    ) {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight.values().any(|running| running.action == *action) {
+            debug!("still running {:?}", action);
+            return;
+        }
+        self.next_action += 1;
+        let id = self.next_action;
+        let cancel = Arc::new(AtomicBool::new(false));
+        in_flight.insert(
+            id,
+            InFlight {
+                action: action.clone(),
+                env: match action {
+                    TUIAction::ChangeEnv(env) | TUIAction::GetSplitPods(env) => *env,
+                    _ => self.kube_env,
+                },
+                cancel: cancel.clone(),
+            },
+        );
+        drop(in_flight);
+        let label = action_label(action);
+        event_tx
+            .send(TUIEvent::ActionStarted(id, label.clone()))
+            .unwrap();
+        let (work_tx, work_rx) = mpsc::channel();
+        let forward_tx = event_tx.clone();
+        thread::spawn(move || {
+            for event in work_rx {
+                if !cancel.load(Ordering::Relaxed) && forward_tx.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        let (in_flight, event_tx) = (self.in_flight.clone(), event_tx.clone());
+        thread::spawn(move || {
+            run_for_action(id, || work(&work_tx, &label));
+            in_flight.lock().unwrap().remove(&id);
+            event_tx.send(TUIEvent::ActionFinished(id)).unwrap();
+        });
+    }
+
+    /// Drops the events of an action in flight and terminates its processes.
+    fn cancel_action(&mut self, id: u64, event_tx: &Sender<TUIEvent>) {
+        let Some(cancelled) = self.in_flight.lock().unwrap().remove(&id) else {
+            return;
+        };
+        cancelled.cancel.store(true, Ordering::Relaxed);
+        thread::spawn(move || {
+            processes().terminate_where(|process| process.action == Some(id), kill_grace())
+        });
+        event_tx.send(TUIEvent::ActionFinished(id)).unwrap();
+        event_tx
+            .send(TUIEvent::ShowHint(format!(
+                "Cancelled {}",
+                action_label(&cancelled.action)
+            )))
+            .unwrap();
+    }
+}
+
+/// How an action in flight is shown.
+fn action_label(action: &TUIAction) -> String {
+    match action {
+        TUIAction::ChangeEnv(env) => format!("connect to {:?}", env),
+        TUIAction::CheckConnectivity => "check connectivity".to_string(),
+        TUIAction::GetPods => "get pods".to_string(),
+        TUIAction::GetTail => "get tail".to_string(),
+        TUIAction::DescribePod(pod) => format!("describe {}", pod),
+        TUIAction::GetDeployments => "get deployments".to_string(),
+        TUIAction::RolloutUndo(deployment) => format!("undo rollout of {}", deployment),
+        TUIAction::GetTop => "get top".to_string(),
+        TUIAction::GetSplitPods(env) => format!("get {:?} pods", env),
+        TUIAction::GetNamespaces => "get namespaces".to_string(),
+        TUIAction::ExportLogs(_) => "export logs".to_string(),
+        _ => format!("{:?}", action),
+    }
+}
+
+pub fn start(
+    event_tx: Sender<TUIEvent>,
+    action_tx: Sender<TUIAction>,
+    action_rx: Receiver<TUIAction>,
+) {
+    let mut scheduler = RefreshScheduler::new();
+    let logs_tx = match timestamps_enabled() {
+        true => {
+            let (logs_tx, logs_rx) = mpsc::channel();
+            let event_tx = event_tx.clone();
+            thread::spawn(move || merge_logs(logs_rx, event_tx, reorder_window()));
+            logs_tx
+        }
+        false => event_tx.clone(),
+    };
+    let mut context = ActionContext {
+        kube_env: KubeEnv::Dev,
+        namespaces: HashMap::new(),
+        streams: HashMap::new(),
+        logs_tx,
+        logs_need_login: Arc::new(AtomicBool::new(false)),
+        action_tx,
+        in_flight: Arc::default(),
+        next_action: 0,
+    };
+    loop {
+        match action_rx.recv_timeout(scheduler.time_until_next()) {
+            Ok(TUIAction::Refresh(action, interval)) => {
+                scheduler.schedule(*action.clone(), interval);
+                handle_action(*action, &mut context, &event_tx);
+            }
+            Ok(TUIAction::CancelRefresh(action)) => scheduler.cancel(&action),
+            Ok(action) => handle_action(action, &mut context, &event_tx),
+            Err(RecvTimeoutError::Timeout) => {
+                for action in scheduler.due() {
+                    handle_action(action, &mut context, &event_tx);
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+}
+
+fn handle_action(action: TUIAction, context: &mut ActionContext, event_tx: &Sender<TUIEvent>) {
+    debug!("handling action: {:?}", action);
+    let kube_context = context.kube_env.data().environment;
+    let namespace = context.namespace(context.kube_env);
+    match action.clone() {
+        TUIAction::ChangeEnv(env) => {
+            let previous = context.kube_env;
+            context.kube_env = env;
+            // the fetches in flight and the streams of the main pane are for the previous one
+            let in_flight: Vec<u64> = context
+                .in_flight
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(_, running)| running.fetches_for(previous))
+                .map(|(id, _)| *id)
+                .collect();
+            for id in in_flight {
+                context.cancel_action(id, event_tx);
+            }
+            let streams: Vec<String> = context
+                .streams
+                .keys()
+                .filter(|name| name.starts_with("logs ") || *name == "events")
+                .cloned()
+                .collect();
+            for name in &streams {
+                context.stop_stream(name);
+            }
+            let namespace = context.namespace(env);
+            let action_tx = context.action_tx.clone();
+            context.run_in_flight(&action, event_tx, move |event_tx, source| {
+                match check_connectivity(env.data(), &namespace, event_tx) {
+                    Ok(_) => {
+                        event_tx.send(TUIEvent::IsConnected).unwrap();
+                        event_tx.send(TUIEvent::ClearError).unwrap();
+                        if streams.iter().any(|name| name.starts_with("logs ")) {
+                            action_tx.send(TUIAction::GetLogs).unwrap();
                         }
-                        Err(_) => {
-                            event_tx.send(TUIEvent::RequestLoginStart).unwrap();
+                        if streams.iter().any(|name| name == "events") {
+                            action_tx.send(TUIAction::GetEvents).unwrap();
                         }
-                    },
+                    }
                     Err(error) => {
-                        on_error(&error, &event_tx);
+                        on_error(source, &error, event_tx);
                         event_tx.send(TUIEvent::RequestLoginStart).unwrap();
                     }
+                }
+            });
+        }
+        TUIAction::CheckConnectivity => {
+            let kube_env = context.kube_env;
+            let (logs_need_login, action_tx) =
+                (context.logs_need_login.clone(), context.action_tx.clone());
+            context.run_in_flight(&action, event_tx, move |event_tx, source| {
+                match check_connectivity(kube_env.data(), &namespace, event_tx) {
+                    Ok(_) => {
+                        event_tx.send(TUIEvent::IsConnected).unwrap();
+                        event_tx.send(TUIEvent::ClearError).unwrap();
+                        if logs_need_login.swap(false, Ordering::Relaxed) {
+                            action_tx.send(TUIAction::GetLogs).unwrap();
+                        }
+                    }
+                    Err(error) => {
+                        on_error(source, &error, event_tx);
+                        event_tx.send(TUIEvent::RequestLoginStart).unwrap();
+                    }
+                }
+            });
+        }
+        TUIAction::LogIn => {
+            let event_tx_clone = event_tx.clone();
+            thread::spawn(move || login(login_command(), &event_tx_clone));
+        }
+        TUIAction::GetLogs => {
+            for (index, source) in log_sources().into_iter().enumerate() {
+                let env = source.env.unwrap_or(context.kube_env);
+                if env != context.kube_env {
+                    if let Err(error) = ensure_context(env.data(), event_tx) {
+                        on_error(&format!("logs of {}", source.name), &error, event_tx);
+                        continue;
+                    }
+                }
+                let namespace = source
+                    .namespace
+                    .clone()
+                    .unwrap_or_else(|| context.namespace(env));
+                let flags = StreamFlags {
+                    stop: context.replace_stream(&format!("logs {}", source.name)),
+                    needs_login: context.logs_need_login.clone(),
+                    ..StreamFlags::default()
+                };
+                let target = LogTarget {
+                    env,
+                    namespace,
+                    source,
+                    index,
                 };
+                let event_tx_clone = context.logs_tx.clone();
+                thread::spawn(move || follow_source(&target, &event_tx_clone, &flags));
             }
-            TUIAction::CheckConnectivity => match check_connectivity(&event_tx) {
-                Ok(_) => {
-                    event_tx.send(TUIEvent::IsConnected).unwrap();
-                    event_tx.send(TUIEvent::ClearError).unwrap();
+        }
+        TUIAction::GetPods => context.run_in_flight(&action, event_tx, move |event_tx, source| {
+            match get_pods(kube_context, &namespace, event_tx) {
+                Ok(output) => {
+                    event_tx.send(TUIEvent::AddPods(output)).unwrap();
                 }
                 Err(error) => {
-                    on_error(&error, &event_tx);
+                    on_error(source, &error, event_tx);
                     event_tx.send(TUIEvent::RequestLoginStart).unwrap();
                 }
-            },
-            TUIAction::LogIn => {
-                let event_tx_clone = event_tx.clone();
-                thread::spawn(move || login(login_command(), &event_tx_clone));
             }
-            TUIAction::GetLogs => {
-                let event_tx_clone = event_tx.clone();
-                thread::spawn(move || {
-                    if let Err(error) =
-                        get_logs(get_logs_command(), &event_tx_clone, |_| false)
-                    {
-                        event_tx_clone
-                            .send(TUIEvent::Error(TUIError::API(error)))
-                            .unwrap();
+        }),
+        TUIAction::GetEvents => {
+            let stop = context.replace_stream("events");
+            let event_tx_clone = event_tx.clone();
+            thread::spawn(move || {
+                if let Err(error) = get_stream(
+                    get_events_command(kube_context, &namespace),
+                    &event_tx_clone,
+                    "events",
+                    |_| stop.load(Ordering::Relaxed),
+                    |events| Some(TUIEvent::AddEvents(events)),
+                ) {
+                    if !stop.load(Ordering::Relaxed) {
+                        on_error("events", &error, &event_tx_clone);
                     }
-                });
+                }
+            });
+        }
+        TUIAction::DescribePod(pod) => context.run_in_flight(
+            &action,
+            event_tx,
+            move |event_tx, source| match describe_pod(&pod, kube_context, &namespace, event_tx) {
+                Ok(output) => {
+                    event_tx.send(TUIEvent::AddPodDescription(output)).unwrap();
+                }
+                Err(error) => on_error(source, &error, event_tx),
+            },
+        ),
+        TUIAction::GetDeployments => context.run_in_flight(
+            &action,
+            event_tx,
+            move |event_tx, source| match get_deployments(kube_context, &namespace, event_tx) {
+                Ok(output) => {
+                    event_tx.send(TUIEvent::AddDeployments(output)).unwrap();
+                }
+                Err(error) => on_error(source, &error, event_tx),
+            },
+        ),
+        TUIAction::RolloutUndo(deployment) => {
+            let action_tx = context.action_tx.clone();
+            context.run_in_flight(
+                &action,
+                event_tx,
+                move |event_tx, source| match rollout_undo(
+                    &deployment,
+                    kube_context,
+                    &namespace,
+                    event_tx,
+                ) {
+                    Ok(_) => action_tx.send(TUIAction::GetDeployments).unwrap(),
+                    Err(error) => on_error(source, &error, event_tx),
+                },
+            )
+        }
+        TUIAction::GetTop => context.run_in_flight(&action, event_tx, move |event_tx, source| {
+            match get_top(kube_context, &namespace, event_tx) {
+                Ok((top, limits)) => {
+                    event_tx.send(TUIEvent::AddPodLimits(limits)).unwrap();
+                    event_tx.send(TUIEvent::AddTop(top)).unwrap();
+                }
+                Err(error) => on_error(source, &error, event_tx),
+            }
+        }),
+        TUIAction::OpenSplit(env) => {
+            handle_action(TUIAction::CloseSplit, context, event_tx);
+            if let Err(error) = ensure_context(env.data(), event_tx) {
+                on_error(&format!("split {:?}", env), &error, event_tx);
+                return;
             }
-            TUIAction::GetPods => {
-                match get_pods() {
+            let split_namespace = context.namespace(env);
+            let stop = context.replace_stream("split logs");
+            let event_tx_clone = event_tx.clone();
+            thread::spawn(move || {
+                if let Err(error) = get_stream(
+                    get_logs_command(
+                        env.data().environment,
+                        &split_namespace,
+                        &log_sources()[0],
+                        None,
+                        &[],
+                        timestamps_enabled(),
+                    ),
+                    &event_tx_clone,
+                    &format!("split {:?} logs", env),
+                    |_| stop.load(Ordering::Relaxed),
+                    |line| Some(TUIEvent::AddSplitLog(env, line)),
+                ) {
+                    debug!("split log stream for {:?} ended: {}", env, error);
+                }
+            });
+        }
+        TUIAction::CloseSplit => context.stop_stream("split logs"),
+        TUIAction::GetSplitPods(env) => {
+            let split_namespace = context.namespace(env);
+            context.run_in_flight(&action, event_tx, move |event_tx, source| {
+                match get_pods(env.data().environment, &split_namespace, event_tx) {
                     Ok(output) => {
-                        event_tx.send(TUIEvent::AddPods(output)).unwrap();
-                    }
-                    Err(error) => {
-                        on_error(&error, &event_tx);
-                        event_tx.send(TUIEvent::RequestLoginStart).unwrap();
+                        event_tx.send(TUIEvent::AddSplitPods(env, output)).unwrap();
                     }
+                    Err(error) => on_error(source, &error, event_tx),
                 }
-            }
-            TUIAction::GetTail => match get_tail(get_tail_command()) {
+            })
+        }
+        TUIAction::GetNamespaces => context.run_in_flight(
+            &action,
+            event_tx,
+            move |event_tx, source| match get_namespaces(kube_context, event_tx) {
+                Ok(output) => {
+                    event_tx.send(TUIEvent::AddNamespaces(output)).unwrap();
+                }
+                Err(error) => on_error(source, &error, event_tx),
+            },
+        ),
+        TUIAction::SetNamespace(env, namespace) => {
+            context.namespaces.insert(env, namespace);
+        }
+        TUIAction::ExportLogs(export) => {
+            context.run_in_flight(&action, event_tx, move |event_tx, source| {
+                let path = data_dir()
+                    .join("exports")
+                    .join(format!("logs-{}.log", now_secs()));
+                let event = match export_logs(&export, &path) {
+                    Ok(lines) => TUIEvent::ShowHint(format!(
+                        "Exported {} lines to {}",
+                        lines,
+                        path.display()
+                    )),
+                    Err(error) => TUIEvent::Error(ErrorReport::new(
+                        source,
+                        TUIError::Export(format!("Exporting the logs failed: {}", error)),
+                    )),
+                };
+                event_tx.send(event).unwrap();
+            })
+        }
+        TUIAction::Refresh(action, _) => handle_action(*action, context, event_tx),
+        TUIAction::CancelRefresh(_) => {}
+        TUIAction::CancelAction(id) => context.cancel_action(id, event_tx),
+        TUIAction::GetTail => context.run_in_flight(&action, event_tx, move |event_tx, source| {
+            match get_tail(get_tail_command()) {
                 Ok(output) => {
                     event_tx.send(TUIEvent::AddTailLog(output)).unwrap();
                 }
                 Err(error) => {
-                    on_error(&error, &event_tx);
+                    on_error(source, &error, event_tx);
                     event_tx.send(TUIEvent::RequestLoginStart).unwrap();
                 }
-            },
-        }
+            }
+        }),
     }
 }
 
@@ -100,26 +507,73 @@ fn login_command() -> Result<Child, Error> {
         .arg("myccv-lab-non-prod-myccv-lab-developer") //config
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
-        .spawn()
+        .spawn_as("aws sso login")
 }
 
-fn get_logs_command() -> Result<Child, Error> {
+/// Follows the logs of a pod of `source`, or of all its pods running when it starts, with the
+/// timestamps of the lines when `timestamps` is set.
+fn get_logs_command(
+    context: &str,
+    namespace: &str,
+    source: &LogSource,
+    pod: Option<&str>,
+    since: &[String],
+    timestamps: bool,
+) -> Result<Child, Error> {
     // Command::new("tail")
     //     .arg("-f")
     //     .arg("src/main.rs")
-    Command::new("kubectl")
+    let mut command = Command::new("kubectl");
+    command
         .arg("logs")
         .arg("-n")
-        .arg("myccv-dev-salespoint") //config
+        .arg(namespace)
+        .arg("--kubeconfig")
+        .arg(kubeconfig())
+        .arg("--context")
+        .arg(context);
+    match pod {
+        Some(pod) => command.arg(format!("pod/{}", pod)),
+        None => command.arg("-l").arg(&source.selector),
+    };
+    if let Some(container) = &source.container {
+        command.arg("-c").arg(container);
+    }
+    command.arg("-f").arg("--prefix=true").args(since);
+    if timestamps {
+        command.arg("--timestamps");
+    }
+    command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn_as(match pod {
+            Some(pod) => format!("logs of pod/{} in {}", pod, context),
+            None => format!("logs of {} in {}", source.selector, context),
+        })
+}
+
+/// Watches the pods of a selector, a line per change: the event, the name, the phase and the
+/// start time of the pod.
+fn get_pod_watch_command(context: &str, namespace: &str, selector: &str) -> Result<Child, Error> {
+    Command::new("kubectl")
+        .arg("get")
+        .arg("pods")
+        .arg("-n")
+        .arg(namespace)
+        .arg("--kubeconfig")
+        .arg(kubeconfig())
+        .arg("--context")
+        .arg(context)
         .arg("-l")
-        .arg("component=salespoint-v2") //config
-        .arg("-c")
-        .arg("salespoint-v2") //config
-        .arg("-f")
-        .arg("--prefix=true")
+        .arg(selector)
+        .arg("--watch")
+        .arg("--output-watch-events")
+        .arg("--no-headers")
+        .arg("-o")
+        .arg("custom-columns=NAME:.metadata.name,PHASE:.status.phase,STARTED:.status.startTime")
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
-        .spawn()
+        .spawn_as(format!("watching the pods of {} in {}", selector, context))
 }
 
 fn get_tail_command() -> Result<Child, Error> {
@@ -127,7 +581,98 @@ fn get_tail_command() -> Result<Child, Error> {
         .arg("logs.txt")
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
-        .spawn()
+        .spawn_as("tail of logs.txt")
+}
+
+fn get_events_command(context: &str, namespace: &str) -> Result<Child, Error> {
+    Command::new("kubectl")
+        .arg("get")
+        .arg("events")
+        .arg("-n")
+        .arg(namespace)
+        .arg("--kubeconfig")
+        .arg(kubeconfig())
+        .arg("--context")
+        .arg(context)
+        .arg("--watch")
+        .arg("-o")
+        .arg("custom-columns=LAST:.lastTimestamp,TYPE:.type,REASON:.reason,OBJECT:.involvedObject.name,MESSAGE:.message")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn_as(format!("watching the events of {} in {}", namespace, context))
+}
+
+fn get_deployments_command(context: &str, namespace: &str) -> Result<Child, Error> {
+    Command::new("kubectl")
+        .arg("get")
+        .arg("deployments")
+        .arg("-n")
+        .arg(namespace)
+        .arg("--kubeconfig")
+        .arg(kubeconfig())
+        .arg("--context")
+        .arg(context)
+        .arg("-o")
+        .arg("custom-columns=NAME:.metadata.name,DESIRED:.spec.replicas,READY:.status.readyReplicas,UPDATED:.status.updatedReplicas,AVAILABLE:.status.availableReplicas,GENERATION:.metadata.generation,OBSERVED:.status.observedGeneration,IMAGES:.spec.template.spec.containers[*].image")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn_as(format!("deployments of {} in {}", namespace, context))
+}
+
+fn rollout_undo_command(deployment: &str, context: &str, namespace: &str) -> Result<Child, Error> {
+    Command::new("kubectl")
+        .arg("rollout")
+        .arg("undo")
+        .arg("-n")
+        .arg(namespace)
+        .arg("--kubeconfig")
+        .arg(kubeconfig())
+        .arg("--context")
+        .arg(context)
+        .arg(format!("deployment/{}", deployment))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn_as(format!(
+            "undoing the rollout of {} in {}",
+            deployment, context
+        ))
+}
+
+fn top_pods_command(context: &str, namespace: &str) -> Result<Child, Error> {
+    Command::new("kubectl")
+        .arg("top")
+        .arg("pods")
+        .arg("-n")
+        .arg(namespace)
+        .arg("--kubeconfig")
+        .arg(kubeconfig())
+        .arg("--context")
+        .arg(context)
+        .arg("--no-headers")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn_as(format!("top of {} in {}", namespace, context))
+}
+
+fn pod_limits_command(context: &str, namespace: &str) -> Result<Child, Error> {
+    Command::new("kubectl")
+        .arg("get")
+        .arg("pods")
+        .arg("-n")
+        .arg(namespace)
+        .arg("--kubeconfig")
+        .arg(kubeconfig())
+        .arg("--context")
+        .arg(context)
+        .arg("-o")
+        .arg(
+            "custom-columns=NAME:.metadata.name,\
+             MEMORY:.spec.containers[*].resources.limits.memory,\
+             CONTAINERS:.spec.containers[*].name",
+        )
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn_as(format!("pod limits of {} in {}", namespace, context))
 }
 
 fn update_kubeconfig_command(kube_env: KubeEnvData) -> Result<Child, Error> {
@@ -138,75 +683,500 @@ fn update_kubeconfig_command(kube_env: KubeEnvData) -> Result<Child, Error> {
         .arg("update-kubeconfig")
         .arg("--name")
         .arg(kube_env.environment) //config
+        .arg("--alias")
+        .arg(kube_env.environment)
+        .arg("--kubeconfig")
+        .arg(kubeconfig())
         // Command::new("cat")
         //     .arg("aws_sso_mock.sh") //config
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
-        .spawn()
+        .spawn_as(format!("update-kubeconfig for {}", kube_env.environment))
 }
 
-fn get_pods_command() -> Result<Child, Error> {
+fn get_contexts_command() -> Result<Child, Error> {
+    Command::new("kubectl")
+        .arg("config")
+        .arg("get-contexts")
+        .arg("--kubeconfig")
+        .arg(kubeconfig())
+        .arg("-o")
+        .arg("name")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn_as("contexts of the kubeconfig")
+}
+
+fn get_server_command(context: &str) -> Result<Child, Error> {
+    Command::new("kubectl")
+        .arg("config")
+        .arg("view")
+        .arg("--kubeconfig")
+        .arg(kubeconfig())
+        .arg("--context")
+        .arg(context)
+        .arg("--minify")
+        .arg("-o")
+        .arg("jsonpath={.clusters[0].cluster.server}")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn_as(format!("API server of {}", context))
+}
+
+fn get_pods_command(context: &str, namespace: &str) -> Result<Child, Error> {
     Command::new("kubectl")
         .arg("get")
         .arg("-n")
-        .arg("myccv-dev-salespoint") //config
+        .arg(namespace)
+        .arg("--kubeconfig")
+        .arg(kubeconfig())
+        .arg("--context")
+        .arg(context)
         .arg("pods")
         // Command::new("cat")
         //     .arg("aws_sso_mock.sh") //config
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
-        .spawn()
+        .spawn_as(format!("pods of {} in {}", namespace, context))
+}
+
+fn describe_pod_command(pod: &str, context: &str, namespace: &str) -> Result<Child, Error> {
+    Command::new("kubectl")
+        .arg("describe")
+        .arg("-n")
+        .arg(namespace)
+        .arg("--kubeconfig")
+        .arg(kubeconfig())
+        .arg("--context")
+        .arg(context)
+        .arg("pod")
+        .arg(pod)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn_as(format!("describing pod {} in {}", pod, context))
+}
+
+fn get_namespaces_command(context: &str) -> Result<Child, Error> {
+    Command::new("kubectl")
+        .arg("get")
+        .arg("namespaces")
+        .arg("--kubeconfig")
+        .arg(kubeconfig())
+        .arg("--context")
+        .arg(context)
+        .arg("--no-headers")
+        .arg("-o")
+        .arg("custom-columns=NAME:.metadata.name")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn_as(format!("namespaces of {}", context))
 }
 
 fn update_kubeconfig(kube_env: KubeEnvData, event_tx: &Sender<TUIEvent>) -> Result<String, String> {
     match update_kubeconfig_command(kube_env) {
-        Ok(child) => wait_for_output_with_timeout(child, event_tx),
+        Ok(child) => {
+            wait_for_output_with_timeout(child, event_tx, Timeouts::of("update_kubeconfig"), None)
+        }
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+/// Waits for a kubectl `command` against `context`, within the timeouts of the command.
+fn wait_for_kubectl(
+    child: Result<Child, Error>,
+    command: &str,
+    context: &str,
+    event_tx: &Sender<TUIEvent>,
+) -> Result<String, String> {
+    match child {
+        Ok(child) => {
+            wait_for_output_with_timeout(child, event_tx, Timeouts::of(command), Some(context))
+        }
         Err(error) => Err(error.to_string()),
     }
 }
 
-fn get_pods() -> Result<String, String> {
-    match get_pods_command() {
-        Ok(child) => wait_for_output(child),
+fn get_pods(context: &str, namespace: &str, event_tx: &Sender<TUIEvent>) -> Result<String, String> {
+    wait_for_kubectl(
+        get_pods_command(context, namespace),
+        "get_pods",
+        context,
+        event_tx,
+    )
+}
+
+fn get_namespaces(context: &str, event_tx: &Sender<TUIEvent>) -> Result<String, String> {
+    wait_for_kubectl(
+        get_namespaces_command(context),
+        "get_namespaces",
+        context,
+        event_tx,
+    )
+}
+
+fn describe_pod(
+    pod: &str,
+    context: &str,
+    namespace: &str,
+    event_tx: &Sender<TUIEvent>,
+) -> Result<String, String> {
+    let child = describe_pod_command(pod, context, namespace);
+    wait_for_kubectl(child, "describe_pod", context, event_tx)
+}
+
+fn get_deployments(
+    context: &str,
+    namespace: &str,
+    event_tx: &Sender<TUIEvent>,
+) -> Result<String, String> {
+    let child = get_deployments_command(context, namespace);
+    wait_for_kubectl(child, "get_deployments", context, event_tx)
+}
+
+fn rollout_undo(
+    deployment: &str,
+    context: &str,
+    namespace: &str,
+    event_tx: &Sender<TUIEvent>,
+) -> Result<String, String> {
+    let child = rollout_undo_command(deployment, context, namespace);
+    wait_for_kubectl(child, "rollout_undo", context, event_tx)
+}
+
+fn get_top(
+    context: &str,
+    namespace: &str,
+    event_tx: &Sender<TUIEvent>,
+) -> Result<(String, String), String> {
+    let top = wait_for_kubectl(
+        top_pods_command(context, namespace),
+        "get_top",
+        context,
+        event_tx,
+    )?;
+    let limits = pod_limits_command(context, namespace);
+    Ok((top, wait_for_kubectl(limits, "get_top", context, event_tx)?))
+}
+
+/// Whether kubectl lists `context` in our own kubeconfig, asked anew every time.
+fn has_context(context: &str, event_tx: &Sender<TUIEvent>) -> bool {
+    let contexts = match get_contexts_command() {
+        Ok(child) => {
+            wait_for_output_with_timeout(child, event_tx, Timeouts::of("get_contexts"), None)
+        }
         Err(error) => Err(error.to_string()),
+    };
+    match contexts {
+        Ok(contexts) => contexts.lines().any(|name| name.trim() == context),
+        Err(error) => {
+            debug!("listing the contexts failed: {}", error);
+            false
+        }
     }
 }
 
-fn check_connectivity(event_tx: &Sender<TUIEvent>) -> Result<String, String> {
-    match get_pods_command() {
-        Ok(child) => wait_for_output_with_timeout(child, event_tx),
+/// The API server of the cluster of `context`, as in our own kubeconfig.
+fn server_of(context: &str, event_tx: &Sender<TUIEvent>) -> Option<String> {
+    let child = get_server_command(context).ok()?;
+    wait_for_output_with_timeout(child, event_tx, Timeouts::of("get_server"), None)
+        .ok()
+        .map(|server| server.trim().to_string())
+        .filter(|server| !server.is_empty())
+}
+
+/// Adds the context of `kube_env` to our own kubeconfig, unless it is already there.
+fn ensure_context(kube_env: KubeEnvData, event_tx: &Sender<TUIEvent>) -> Result<(), String> {
+    if has_context(kube_env.environment, event_tx) {
+        return Ok(());
+    }
+    update_kubeconfig(kube_env, event_tx).map(|_| ())
+}
+
+fn check_connectivity(
+    kube_env: KubeEnvData,
+    namespace: &str,
+    event_tx: &Sender<TUIEvent>,
+) -> Result<String, String> {
+    ensure_context(kube_env, event_tx)?;
+    match get_pods_command(kube_env.environment, namespace) {
+        Ok(child) => wait_for_output_with_timeout(
+            child,
+            event_tx,
+            Timeouts::of("check_connectivity"),
+            Some(kube_env.environment),
+        ),
         Err(error) => Err(error.to_string()),
     }
 }
 
+/// Follows the lines of the log source at `source`, sending the ones `keep` keeps, without their
+/// timestamps when `timestamps` is off.
 fn get_logs(
     child: Result<Child, Error>,
     event_tx: &Sender<TUIEvent>,
-    timeout_fn: fn(Instant) -> bool,
+    name: &str,
+    timeout_fn: impl Fn(Instant) -> bool,
+    source: usize,
+    mut keep: impl FnMut(&str) -> bool,
+) -> Result<(), String> {
+    let timestamps = timestamps_enabled();
+    get_stream(child, event_tx, name, timeout_fn, |line| {
+        let line = match keep(&line) {
+            true if timestamps => line,
+            true => strip_timestamp(line),
+            false => return None,
+        };
+        Some(TUIEvent::AddLog(source, line))
+    })
+}
+
+/// Flags shared by the streams following a log source.
+#[derive(Clone, Default)]
+struct StreamFlags {
+    /// Kills the stream right away.
+    stop: Arc<AtomicBool>,
+    /// Lets the stream end on its own and not reconnect, like once its pod terminated.
+    detached: Arc<AtomicBool>,
+    /// Set when the login expired, to follow the logs again once connected.
+    needs_login: Arc<AtomicBool>,
+}
+
+/// Runs `follow` until stopped, again with a backoff each time it ends, showing the attempts as
+/// the status of the log source at `index`. `follow` is given the attempt and tells whether any
+/// line came. Stops for good when the login expired, asking for a new one.
+fn supervise(
+    name: &str,
+    index: usize,
+    event_tx: &Sender<TUIEvent>,
+    flags: &StreamFlags,
+    mut follow: impl FnMut(u32) -> (Result<(), String>, bool),
+) {
+    let mut backoff = Backoff::from_config();
+    loop {
+        let (result, received) = follow(backoff.attempt());
+        if flags.stop.load(Ordering::Relaxed) || flags.detached.load(Ordering::Relaxed) {
+            return;
+        }
+        if received {
+            backoff.reset();
+        }
+        let error = result
+            .err()
+            .unwrap_or_else(|| "the stream ended".to_string());
+        debug!("{} ended: {}", name, error);
+        if auth_expired(&error) {
+            flags.needs_login.store(true, Ordering::Relaxed);
+            let status = "login expired, log in again to resume".to_string();
+            event_tx
+                .send(TUIEvent::LogStreamStatus(index, Some(status)))
+                .unwrap();
+            event_tx.send(TUIEvent::RequestLoginStart).unwrap();
+            return;
+        }
+        let delay = backoff.next_delay();
+        let status = format!(
+            "{} reconnecting in {}s, attempt {}",
+            name,
+            delay.as_secs_f32(),
+            backoff.attempt()
+        );
+        event_tx
+            .send(TUIEvent::LogStreamStatus(index, Some(status)))
+            .unwrap();
+        let wake = Instant::now() + delay;
+        while Instant::now() < wake {
+            if flags.stop.load(Ordering::Relaxed) || flags.detached.load(Ordering::Relaxed) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        let status = format!("{} reconnected, attempt {}", name, backoff.attempt());
+        event_tx
+            .send(TUIEvent::LogStreamStatus(index, Some(status)))
+            .unwrap();
+    }
+}
+
+/// A log source as followed in an environment and namespace.
+#[derive(Clone)]
+struct LogTarget {
+    env: KubeEnv,
+    namespace: String,
+    source: LogSource,
+    /// Index of the source in `log_sources`.
+    index: usize,
+}
+
+/// Watches the pods of a log source, following the logs of each running one and marking in the
+/// logs where pods started and terminated.
+fn follow_source(target: &LogTarget, event_tx: &Sender<TUIEvent>, flags: &StreamFlags) {
+    let mut pods: HashMap<String, Arc<AtomicBool>> = HashMap::new();
+    let mut watch = PodWatch::new(now_secs());
+    event_tx
+        .send(TUIEvent::LogStreamStatus(target.index, None))
+        .unwrap();
+    supervise("pod watch", target.index, event_tx, flags, |attempt| {
+        let mut received = false;
+        let result = get_stream(
+            get_pod_watch_command(
+                target.env.data().environment,
+                &target.namespace,
+                &target.source.selector,
+            ),
+            event_tx,
+            &format!("pod watch of {}", target.source.name),
+            |_| flags.stop.load(Ordering::Relaxed),
+            |line| {
+                if !received && attempt > 0 {
+                    event_tx
+                        .send(TUIEvent::LogStreamStatus(target.index, None))
+                        .unwrap();
+                }
+                received = true;
+                match watch.apply(&line)? {
+                    PodChange::Started { pod, new } => {
+                        let pod_flags = StreamFlags {
+                            detached: Arc::new(AtomicBool::new(false)),
+                            ..flags.clone()
+                        };
+                        pods.insert(pod.clone(), pod_flags.detached.clone());
+                        let marker = TUIEvent::AddLog(target.index, pod_marker(&pod, "started"));
+                        let (target, event_tx) = (target.clone(), event_tx.clone());
+                        thread::spawn(move || {
+                            follow_pod(&target, &pod, new, &event_tx, &pod_flags)
+                        });
+                        Some(marker)
+                    }
+                    PodChange::Terminated(pod) => {
+                        if let Some(detached) = pods.remove(&pod) {
+                            detached.store(true, Ordering::Relaxed);
+                        }
+                        None
+                    }
+                }
+            },
+        );
+        (result, received)
+    });
+    for detached in pods.values() {
+        detached.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Follows the logs of a pod of a log source, from its start when it is `new` or from its last
+/// lines, reconnecting from where it got to until it is detached.
+fn follow_pod(
+    target: &LogTarget,
+    pod: &str,
+    new: bool,
+    event_tx: &Sender<TUIEvent>,
+    flags: &StreamFlags,
+) {
+    let mut resume = StreamResume::new(Instant::now());
+    let name = format!("pod/{}", pod);
+    supervise(&name, target.index, event_tx, flags, |attempt| {
+        let since = match (attempt, new) {
+            (0, true) => vec![],
+            (0, false) => vec!["--tail=10".to_string()],
+            _ => resume.since_args(Instant::now()),
+        };
+        let mut received = false;
+        let result = get_logs(
+            get_logs_command(
+                target.env.data().environment,
+                &target.namespace,
+                &target.source,
+                Some(pod),
+                &since,
+                true,
+            ),
+            event_tx,
+            &name,
+            |_| flags.stop.load(Ordering::Relaxed),
+            target.index,
+            |line| {
+                if !received && attempt > 0 {
+                    event_tx
+                        .send(TUIEvent::LogStreamStatus(target.index, None))
+                        .unwrap();
+                }
+                received = true;
+                resume.keep(line, Instant::now())
+            },
+        );
+        // gone while the watch was reconnecting, no event will tell it terminated
+        if result
+            .as_ref()
+            .is_err_and(|error| error.contains("NotFound"))
+        {
+            flags.detached.store(true, Ordering::Relaxed);
+            resume.forget(pod);
+        }
+        (result, received)
+    });
+    if !flags.stop.load(Ordering::Relaxed) {
+        event_tx
+            .send(TUIEvent::AddLog(
+                target.index,
+                pod_marker(pod, "terminated"),
+            ))
+            .unwrap();
+    }
+}
+
+/// Follows the output of a long living child, sending every line as the event built by
+/// `to_event`, if any. Fails with the last line of its stderr, if any.
+pub(crate) fn get_stream(
+    child: Result<Child, Error>,
+    event_tx: &Sender<TUIEvent>,
+    name: &str,
+    timeout_fn: impl Fn(Instant) -> bool,
+    mut to_event: impl FnMut(String) -> Option<TUIEvent>,
 ) -> Result<(), String> {
     return if let Ok(mut child) = child {
         let now = Instant::now();
         let mut has_error = false;
+        let mut last_error = None;
         let child_stdout = open_child_stdout(&mut child);
         let child_stderr = open_child_stderr(&mut child);
-        debug!("open_log_channel for get_logs");
+        debug!("open_log_channel for get_stream");
         let (thread_handle, read_stdout_rx, read_stderr_rx) =
             open_log_channel(child_stdout, child_stderr);
-        while !thread_handle.is_finished() {
-            if timeout_fn(now) {
-                child.kill().unwrap();
+        let mut terminated = false;
+        loop {
+            // lines read before the readers stopped are still handled below
+            let finished = thread_handle.is_finished();
+            if !terminated && timeout_fn(now) {
+                terminated = true;
+                if let Err(error) = terminate(&mut child, kill_grace()) {
+                    debug!("terminating the stream failed: {}", error);
+                }
             }
-            if let Ok(error) = read_stderr_rx.recv_timeout(Duration::from_millis(10)) {
-                on_error(&error, &event_tx);
+            for error in read_stderr_rx.try_iter() {
+                let mut report = ErrorReport::new(name, TUIError::API(error.clone()));
+                // the stream goes on or is reconnected
+                report.severity = Severity::Warning;
+                event_tx.send(TUIEvent::Error(report)).unwrap();
                 has_error = true;
+                last_error = Some(error.trim().to_string());
             }
+            // waits for the next line only once the ones already read are handled
             if let Ok(line) = read_stdout_rx.recv_timeout(Duration::from_millis(10)) {
-                add_logs(&event_tx, &line);
+                for line in std::iter::once(line).chain(read_stdout_rx.try_iter()) {
+                    if let Some(event) = to_event(line) {
+                        event_tx.send(event).unwrap();
+                    }
+                }
+            }
+            if finished {
+                break;
             }
         }
-        if !child.wait().unwrap().success() || has_error {
+        let success = child.wait().unwrap().success();
+        processes().exited(child.id());
+        if !success || has_error {
             debug!("child had errors");
-            Err("process experienced some errors".to_string())
+            Err(last_error.unwrap_or_else(|| "process experienced some errors".to_string()))
         } else {
             debug!("child had no errors");
             Ok(())
@@ -216,46 +1186,84 @@ fn get_logs(
     };
 }
 
+/// Writes `export` to a new file at `path`, returns the number of lines written.
+fn export_logs(export: &LogExport, path: &Path) -> io::Result<usize> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut out = BufWriter::new(File::create(path)?);
+    let lines = export.write(&mut out)?;
+    out.flush()?;
+    Ok(lines)
+}
+
 fn get_tail(child: Result<Child, std::io::Error>) -> Result<String, String> {
     wait_for_output(child.unwrap())
 }
 
+/// Waits for the output of `child`, terminating it after `timeouts.limit`. When it is slow, the
+/// API server of `context` is probed to tell in the header what's wrong, or the VPN is blamed
+/// without one.
 fn wait_for_output_with_timeout(
     mut child: Child,
     event_tx: &Sender<TUIEvent>,
+    timeouts: Timeouts,
+    context: Option<&str>,
 ) -> Result<String, String> {
     let now = Instant::now();
+    let pid = child.id();
+    let (stdout, stderr) = (
+        read_to_end(child.stdout.take()),
+        read_to_end(child.stderr.take()),
+    );
     let mut result: Option<Result<String, String>> = None;
     let mut send_error = true;
     while result == None {
         match child.try_wait() {
             Ok(Some(status)) => {
                 debug!("wait with timeout finished {:?}", status.to_string());
-                if let true = status.success() {
-                    result = match child.wait_with_output() {
-                        Ok(output) => Some(Ok(str::from_utf8(&output.stdout).unwrap().to_string())),
-                        Err(_) => Some(Err("Error wait_with_output".to_string())),
-                    };
-                    break;
+                let output = |pipe: JoinHandle<Vec<u8>>| {
+                    String::from_utf8_lossy(&pipe.join().unwrap_or_default()).to_string()
+                };
+                if status.success() {
+                    result = Some(Ok(output(stdout)));
                 } else {
-                    result = Some(Err(
-                        "Exit code ".to_string() + &status.code().unwrap().to_string()
-                    ));
+                    let stderr = output(stderr);
+                    result = Some(Err(match status.code() {
+                        _ if !stderr.trim().is_empty() => stderr,
+                        Some(code) => "Exit code ".to_string() + &code.to_string(),
+                        None => status.to_string(),
+                    }));
                 }
+                break;
             }
             Ok(None) => {
                 trace!("wait with timeout still waiting");
-                if now.elapsed().as_secs() > 1 {
-                    if send_error {
-                        send_error = false;
+                if send_error && now.elapsed() > timeouts.slow {
+                    send_error = false;
+                    let server = context.and_then(|context| server_of(context, event_tx));
+                    let error = match server {
+                        Some(server) => diagnose(&server).map(TUIError::Unreachable),
+                        None => Some(TUIError::VPN),
+                    };
+                    if let Some(error) = error {
                         event_tx
-                            .send(TUIEvent::Error(TUIError::VPN))
+                            .send(TUIEvent::Error(ErrorReport::new(
+                                "connectivity probe",
+                                error,
+                            )))
                             .unwrap();
                     }
                 }
-                if now + Duration::from_secs(60) < Instant::now() {
+                if now + timeouts.limit < Instant::now() {
                     debug!("wait with timeout timed out");
-                    result = Some(Err("timeout".to_string()));
+                    if let Err(error) = terminate(&mut child, kill_grace()) {
+                        debug!("terminating after the timeout failed: {}", error);
+                    }
+                    result = Some(Err(format!(
+                        "timed out after {}s",
+                        timeouts.limit.as_secs()
+                    )));
                 };
                 thread::sleep(Duration::from_millis(100))
             }
@@ -265,21 +1273,38 @@ fn wait_for_output_with_timeout(
             }
         };
     }
+    processes().exited(pid);
     result.unwrap_or(Ok("nothing".to_string()))
 }
 
+/// Reads a pipe of a child to the end on a thread of its own, so the child never blocks on a full
+/// pipe while it is waited for.
+fn read_to_end(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = vec![];
+        if let Some(mut pipe) = pipe {
+            if let Err(error) = pipe.read_to_end(&mut bytes) {
+                debug!("reading the output failed: {}", error);
+            }
+        }
+        bytes
+    })
+}
+
 fn wait_for_output(child: Child) -> Result<String, String> {
+    let pid = child.id();
     let process = child.wait_with_output();
+    processes().exited(pid);
     match process {
         Err(err) => {
             // did not reach this part so far...
-            Err("Unknown error: {:?}".to_string() + &err.to_string())
+            Err("Unknown error: ".to_string() + &err.to_string())
         }
         Ok(output) => {
             if output.status.success() {
                 Ok(str::from_utf8(&output.stdout).unwrap().to_string())
             } else {
-                Err("Error: {:?}".to_string() + str::from_utf8(output.stderr.as_slice()).unwrap())
+                Err(String::from_utf8_lossy(&output.stderr).to_string())
             }
         }
     }
@@ -291,22 +1316,32 @@ fn login(child: Result<Child, Error>, event_tx: &Sender<TUIEvent>) {
         let child_stderr = open_child_stderr(&mut child);
         let (thread_handle, read_stdout_rx, read_stderr_rx) =
             open_log_channel(child_stdout, child_stderr);
-        while !thread_handle.is_finished() {
+        loop {
+            // lines read before the readers stopped are still handled below
+            let finished = thread_handle.is_finished();
             if let Ok(error) = read_stderr_rx.recv_timeout(Duration::from_millis(10)) {
-                on_error(&error, &event_tx);
+                on_error("login", &error, &event_tx);
             }
-            if let Ok(line) = read_stdout_rx.recv_timeout(Duration::from_millis(10)) {
+            for line in read_stdout_rx.try_iter() {
                 add_login_logs(&event_tx, &line);
                 check_login_status(&line, &event_tx);
             }
+            if finished {
+                break;
+            }
             thread::sleep(Duration::from_millis(10));
         }
+        let _ = child.wait();
+        processes().exited(child.id());
     }
 }
 
-fn on_error(error: &str, event_tx: &Sender<TUIEvent>) {
+fn on_error(source: &str, error: &str, event_tx: &Sender<TUIEvent>) {
     event_tx
-        .send(TUIEvent::Error(TUIError::API(error.to_string())))
+        .send(TUIEvent::Error(ErrorReport::new(
+            source,
+            TUIError::API(error.to_string()),
+        )))
         .unwrap();
 }
 
@@ -392,7 +1427,6 @@ fn open_log_channel(
                         text_in_stdout_buf.clear()
                     }
                 }
-                thread::sleep(Duration::from_millis(10));
             }
         });
         while !should_break {
@@ -426,8 +1460,24 @@ fn add_login_logs(event_tx: &Sender<TUIEvent>, line: &str) {
     event_tx.send(TUIEvent::AddLoginLog(line.to_string())).unwrap();
 }
 
-fn add_logs(event_tx: &Sender<TUIEvent>, line: &str) {
-    event_tx.send(TUIEvent::AddLog(line.to_string())).unwrap();
+#[test]
+fn test_env_change_cancels_the_fetches_of_the_previous_env() {
+    let in_flight = |action, env| InFlight {
+        action,
+        env,
+        cancel: Arc::default(),
+    };
+    assert!(in_flight(TUIAction::GetPods, KubeEnv::Dev).fetches_for(KubeEnv::Dev));
+    assert!(in_flight(TUIAction::ChangeEnv(KubeEnv::Dev), KubeEnv::Dev).fetches_for(KubeEnv::Dev));
+    assert!(!in_flight(TUIAction::GetPods, KubeEnv::Prod).fetches_for(KubeEnv::Dev));
+    assert!(
+        !in_flight(TUIAction::GetSplitPods(KubeEnv::Dev), KubeEnv::Dev).fetches_for(KubeEnv::Dev)
+    );
+    assert!(
+        !in_flight(TUIAction::RolloutUndo("api".to_string()), KubeEnv::Dev)
+            .fetches_for(KubeEnv::Dev)
+    );
+    assert!(!in_flight(TUIAction::GetTail, KubeEnv::Dev).fetches_for(KubeEnv::Dev));
 }
 
 #[test]
@@ -482,15 +1532,16 @@ fn test_login_fail() {
 
     thread::spawn(move || login(child, &event_tx));
 
-    let check_events = vec![TUIEvent::Error(TUIError::API(
-        "this is an unusual error\n".to_string(),
-    ))];
+    let check_events = vec![(
+        "login".to_string(),
+        TUIError::API("this is an unusual error\n".to_string()),
+    )];
 
     let mut events = vec![];
 
     while events != check_events {
-        if let Ok(event) = event_rx.recv_timeout(Duration::from_millis(10)) {
-            events.push(event);
+        if let Ok(TUIEvent::Error(report)) = event_rx.recv_timeout(Duration::from_millis(10)) {
+            events.push((report.source, report.error));
         }
     }
 
@@ -509,14 +1560,14 @@ fn test_open_log_channel() {
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn();
-    if let Err(err) = get_logs(child, &event_tx, |_| false) {
+    if let Err(err) = get_logs(child, &event_tx, "test", |_| false, 0, |_| true) {
         error = Some(err);
     } else {
         assert!(false, "{:?}", false);
         error = Some("good".to_string());
     }
     let mut events = vec![];
-    let check_events = vec![TUIEvent::AddLog("Beginning...\n".to_string())];
+    let check_events = vec![TUIEvent::AddLog(0, "Beginning...\n".to_string())];
 
     while events != check_events {
         if let Ok(event) = event_rx.recv_timeout(Duration::from_millis(10)) {
@@ -547,20 +1598,20 @@ fn test_get_logs() {
             .spawn();
         let timeout_fn: fn(Instant) -> bool =
             |now| now + Duration::from_millis(300) < Instant::now();
-        get_logs(child, &event_tx, timeout_fn).unwrap();
+        get_logs(child, &event_tx, "test", timeout_fn, 0, |_| true).unwrap();
     });
     let mut events = vec![];
     let mut actions = vec![];
-    let check_events = vec![TUIEvent::AddLog("Lorem ipsum dolor sit amet, consectetur adipiscing elit. Mauris vitae efficitur elit, sit amet euismod magna. \n".to_string()),
-TUIEvent::AddLog("Nulla mattis eros vel erat varius elementum a nec ex. \n".to_string()),
-TUIEvent::AddLog("Vestibulum ante ipsum primis in faucibus orci luctus et ultrices posuere cubilia curae; Donec sit amet porttitor lorem. \n".to_string()),
-TUIEvent::AddLog("Pellentesque consectetur orci sit amet turpis auctor, ac pretium arcu consectetur. \n".to_string()),
-TUIEvent::AddLog("Duis blandit nisl non sem mattis, eget mattis enim lacinia. \n".to_string()),
-TUIEvent::AddLog("Cras vestibulum efficitur lacus. Vivamus ac ultrices libero. \n".to_string()),
-TUIEvent::AddLog("Integer venenatis convallis massa vitae tempus. Pellentesque a commodo lectus, ac maximus lectus. \n".to_string()),
-TUIEvent::AddLog("Quisque ex magna, vulputate nec porttitor sed, ullamcorper sit amet nisi. \n".to_string()),
-TUIEvent::AddLog("Nullam placerat metus lectus, congue commodo mi commodo in. \n".to_string()),
-TUIEvent::AddLog("Nullam volutpat magna ut leo auctor, sollicitudin pharetra tellus malesuada.\n".to_string())];
+    let check_events = vec![TUIEvent::AddLog(0, "Lorem ipsum dolor sit amet, consectetur adipiscing elit. Mauris vitae efficitur elit, sit amet euismod magna. \n".to_string()),
+TUIEvent::AddLog(0, "Nulla mattis eros vel erat varius elementum a nec ex. \n".to_string()),
+TUIEvent::AddLog(0, "Vestibulum ante ipsum primis in faucibus orci luctus et ultrices posuere cubilia curae; Donec sit amet porttitor lorem. \n".to_string()),
+TUIEvent::AddLog(0, "Pellentesque consectetur orci sit amet turpis auctor, ac pretium arcu consectetur. \n".to_string()),
+TUIEvent::AddLog(0, "Duis blandit nisl non sem mattis, eget mattis enim lacinia. \n".to_string()),
+TUIEvent::AddLog(0, "Cras vestibulum efficitur lacus. Vivamus ac ultrices libero. \n".to_string()),
+TUIEvent::AddLog(0, "Integer venenatis convallis massa vitae tempus. Pellentesque a commodo lectus, ac maximus lectus. \n".to_string()),
+TUIEvent::AddLog(0, "Quisque ex magna, vulputate nec porttitor sed, ullamcorper sit amet nisi. \n".to_string()),
+TUIEvent::AddLog(0, "Nullam placerat metus lectus, congue commodo mi commodo in. \n".to_string()),
+TUIEvent::AddLog(0, "Nullam volutpat magna ut leo auctor, sollicitudin pharetra tellus malesuada.\n".to_string())];
 
     while events != check_events {
         if let Ok(event) = event_rx.recv_timeout(Duration::from_millis(10)) {
@@ -589,13 +1640,20 @@ fn test_wait_with_output_timeout() {
         .spawn();
     let (event_tx, event_rx): (Sender<TUIEvent>, Receiver<TUIEvent>) = mpsc::channel();
     let mut events = vec![];
-    let check_events = vec![TUIEvent::Error(TUIError::VPN)];
+    let check_events = vec![("connectivity probe".to_string(), TUIError::VPN)];
     match child {
-        Ok(child) => match wait_for_output_with_timeout(child, &event_tx) {
+        Ok(child) => match wait_for_output_with_timeout(
+            child,
+            &event_tx,
+            Timeouts::new(Duration::from_secs(1), Duration::from_secs(60)),
+            None,
+        ) {
             Ok(output) => {
                 while events.is_empty() {
-                    if let Ok(event) = event_rx.recv_timeout(Duration::from_millis(10)) {
-                        events.push(event);
+                    if let Ok(TUIEvent::Error(report)) =
+                        event_rx.recv_timeout(Duration::from_millis(10))
+                    {
+                        events.push((report.source, report.error));
                     }
                 }
                 assert!(events == check_events, "events was: {:?}", events);
@@ -607,6 +1665,25 @@ fn test_wait_with_output_timeout() {
     }
 }
 
+#[test]
+fn test_wait_with_output_timeout_reads_large_output() {
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg("head -c 200000 /dev/zero; echo failed >&2; exit 3")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let (event_tx, _event_rx): (Sender<TUIEvent>, Receiver<TUIEvent>) = mpsc::channel();
+    let timeouts = Timeouts::new(Duration::from_secs(5), Duration::from_secs(5));
+    let result = wait_for_output_with_timeout(child, &event_tx, timeouts, None);
+    assert!(
+        result == Err("failed\n".to_string()),
+        "result was: {:?}",
+        result
+    );
+}
+
 #[test]
 fn test_wait_with_output_timeout_fail() {
     crate::init_logging().unwrap();
@@ -618,7 +1695,12 @@ fn test_wait_with_output_timeout_fail() {
         .spawn();
     let (event_tx, _): (Sender<TUIEvent>, Receiver<TUIEvent>) = mpsc::channel();
     match child {
-        Ok(child) => match wait_for_output_with_timeout(child, &event_tx) {
+        Ok(child) => match wait_for_output_with_timeout(
+            child,
+            &event_tx,
+            Timeouts::new(Duration::from_secs(5), Duration::from_secs(60)),
+            None,
+        ) {
             Ok(_) => {}
             Err(error) => {
                 assert!(error == "Exit code 1".to_string(), "error was: {:?}", error);
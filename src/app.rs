@@ -1,28 +1,170 @@
 use std::{
     io::{self},
-    sync::mpsc::{Receiver, Sender},
-    time::Duration,
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::{Duration, Instant},
 };
 
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use log::debug;
 use ratatui::{
     backend::Backend,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::Rect,
     widgets::{Block, Borders, Paragraph},
     Terminal,
 };
 
 use crate::{
-    structs::{Direction2, KubeEnv, Store, TUIAction, TUIError, TUIEvent, UserInput},
-    ui::{MainLayoutUI, SingleLayoutUI, UI},
-    widgets::RenderWidget,
+    errors::errors_text,
+    keybindings::{KeyChord, KeyContext, LOGIN_ACTIONS},
+    processes::{processes, processes_text},
+    scheduler::{frame_interval, RenderScheduler},
+    structs::{Direction2, KubeEnv, Store, TUIAction, TUIEvent},
+    ui::{centered_rect, popup_end_scroll, MainLayoutUI, SingleLayoutUI, UI},
+    widget_state::HeaderState,
+    widgets::{CliWidgetId, Command, Keymap, RenderWidget},
 };
 
+/// Lines a log view moves per turn of the mouse wheel.
+const SCROLL_LINES: i32 = 3;
+/// How often the elapsed times of processes and actions in flight are redrawn.
+const ELAPSED_REFRESH: Duration = Duration::from_secs(1);
+
+fn contains(rect: Rect, column: u16, row: u16) -> bool {
+    column >= rect.x && column < rect.right() && row >= rect.y && row < rect.bottom()
+}
+
+/// Palette entries for the keys handled by the app itself.
+pub fn global_commands() -> Vec<Command> {
+    vec![
+        Command::new(
+            "command-palette",
+            "Open the command palette",
+            |_, event_tx| event_tx.send(TUIEvent::OpenPalette).unwrap(),
+        ),
+        Command::new(
+            "show-help",
+            "Show the keys of the current context",
+            |_, event_tx| event_tx.send(TUIEvent::ToggleHelp).unwrap(),
+        ),
+        Command::new(
+            "show-processes",
+            "Show the child processes running",
+            |_, event_tx| event_tx.send(TUIEvent::ToggleProcesses).unwrap(),
+        ),
+        Command::new(
+            "show-errors",
+            "Show the history of the errors",
+            |_, event_tx| event_tx.send(TUIEvent::ToggleErrors).unwrap(),
+        ),
+        Command::new(
+            "next-action",
+            "Select the next action in flight",
+            |_, event_tx| event_tx.send(TUIEvent::SelectNextAction).unwrap(),
+        ),
+        Command::new(
+            "cancel-action",
+            "Cancel the selected action in flight",
+            |_, event_tx| event_tx.send(TUIEvent::CancelSelectedAction).unwrap(),
+        ),
+        Command::new("quit", "Quit", |_, event_tx| {
+            event_tx.send(TUIEvent::Quit).unwrap()
+        }),
+        Command::new("change-env", "Pick another env", |_, event_tx| {
+            event_tx.send(TUIEvent::RequestEnvChange).unwrap()
+        }),
+        Command::new("switch-to-dev", "Switch to the Dev env", |_, event_tx| {
+            event_tx.send(TUIEvent::EnvChange(KubeEnv::Dev)).unwrap()
+        }),
+        Command::new("switch-to-prod", "Switch to the Prod env", |_, event_tx| {
+            event_tx.send(TUIEvent::EnvChange(KubeEnv::Prod)).unwrap()
+        }),
+        Command::new(
+            "focus-left",
+            "Focus the widget on the left",
+            |_, event_tx| event_tx.send(TUIEvent::Navigate(Direction2::Left)).unwrap(),
+        ),
+        Command::new(
+            "focus-down",
+            "Move down in the focused widget",
+            |_, event_tx| event_tx.send(TUIEvent::Navigate(Direction2::Down)).unwrap(),
+        ),
+        Command::new(
+            "focus-up",
+            "Move up in the focused widget",
+            |_, event_tx| event_tx.send(TUIEvent::Navigate(Direction2::Up)).unwrap(),
+        ),
+        Command::new(
+            "focus-right",
+            "Focus the widget on the right",
+            |_, event_tx| {
+                event_tx
+                    .send(TUIEvent::Navigate(Direction2::Right))
+                    .unwrap()
+            },
+        ),
+    ]
+}
+
+/// Lists the keys that work right now: the global ones and those of the current context.
+fn help_text(store: &Store) -> String {
+    let mut sections = vec![(KeyContext::Global, "Everywhere".to_string())];
+    if store.env_change_possible {
+        sections.push((KeyContext::EnvPicker, "Changing env".to_string()));
+    } else if store.request_login {
+        sections.push((KeyContext::Login, "Can't reach your resources".to_string()));
+    } else if store.logged_in {
+        let focused = [
+            &store.logs_widget,
+            &store.pods_widget,
+            &store.deployments_widget,
+            &store.events_widget,
+            &store.split_logs_widget,
+            &store.split_pods_widget,
+        ]
+        .into_iter()
+        .flatten()
+        .find(|widget| widget.get_widget().is_selected)
+        .and_then(|widget| widget.get_widget().title.clone());
+        sections.push((
+            KeyContext::Widget,
+            match focused {
+                Some(title) => format!("Widgets ({} focused)", title),
+                None => "Widgets".to_string(),
+            },
+        ));
+    }
+    let describe = |action: &str| -> String {
+        store
+            .commands
+            .iter()
+            .find(|command| command.id == action)
+            .map(|command| command.description)
+            .or_else(|| {
+                LOGIN_ACTIONS
+                    .iter()
+                    .find(|(id, _)| *id == action)
+                    .map(|(_, description)| *description)
+            })
+            .unwrap_or(action)
+            .to_string()
+    };
+    let mut text = String::new();
+    for (context, title) in sections {
+        text += &format!("\n{}\n", title);
+        for (action, keys) in store.keybindings.actions(context) {
+            text += &format!("{:>12}  {}\n", keys, describe(action));
+        }
+    }
+    text
+}
+
 struct ThreadManage {
     logs_thread_started: bool,
     pods_thread_started: bool,
     tail_thread_started: bool,
+    events_thread_started: bool,
+    deployments_thread_started: bool,
 }
 
 impl ThreadManage {
@@ -30,11 +172,15 @@ impl ThreadManage {
         logs_thread_started: bool,
         pods_thread_started: bool,
         tail_thread_started: bool,
+        events_thread_started: bool,
+        deployments_thread_started: bool,
     ) -> Self {
         ThreadManage {
             logs_thread_started,
             pods_thread_started,
             tail_thread_started,
+            events_thread_started,
+            deployments_thread_started,
         }
     }
 }
@@ -47,7 +193,7 @@ where
     terminal: &'a mut Terminal<B>,
     event_tx: Sender<TUIEvent>,
     action_tx: Sender<TUIAction>,
-    extended_keymap: &'a Vec<fn(KeyCode, &Store, &Sender<TUIEvent>)>,
+    extended_keymap: &'a Vec<Keymap>,
 }
 
 impl<'a, B: Backend> App<'a, B> {
@@ -55,7 +201,7 @@ impl<'a, B: Backend> App<'a, B> {
         terminal: &'a mut Terminal<B>,
         event_tx: Sender<TUIEvent>,
         action_tx: Sender<TUIAction>,
-        extended_keymap: &'a Vec<fn(KeyCode, &Store, &Sender<TUIEvent>)>,
+        extended_keymap: &'a Vec<Keymap>,
     ) -> Self {
         App {
             is_running: true,
@@ -66,44 +212,93 @@ impl<'a, B: Backend> App<'a, B> {
         }
     }
 
+    /// Draws the store whenever it or the terminal changed, at most `[ui] fps` times a second.
+    /// Without changes the loop sleeps until the next snapshot or input arrives.
     pub fn run_app(&mut self, store_rx: Receiver<Store>) -> io::Result<()> {
-        let mut store_presenter = StorePresenter::init(
+        let Ok(store) = store_rx.recv() else {
+            return Ok(());
+        };
+        let updates_rx = forward_updates(store_rx);
+        let mut store_presenter = StorePresenter::new(
             &mut self.terminal,
             self.extended_keymap,
-            &store_rx,
+            &updates_rx,
             &self.event_tx,
             &self.action_tx,
-        )
-        .unwrap();
+            store,
+        );
+        let mut render_scheduler = RenderScheduler::new(frame_interval());
         while self.is_running {
             store_presenter.initiate_threads();
-            if let Some(input) = store_presenter.handle_user_input() {
-                match input {
-                    UserInput::Quit => {
-                        debug!("Exiting");
-                        self.is_running = false;
+            if render_scheduler.draw_now(Instant::now()) {
+                store_presenter.present();
+            }
+            // the processes panel and the actions in flight show how long they run
+            let refresh = store_presenter.shows_elapsed().then_some(ELAPSED_REFRESH);
+            let update = match render_scheduler.wait(Instant::now()).or(refresh) {
+                Some(timeout) => store_presenter.updates_rx.recv_timeout(timeout),
+                None => store_presenter
+                    .updates_rx
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match update {
+                Ok(update) => {
+                    if store_presenter.update(update) {
+                        render_scheduler.changed();
                     }
-                    UserInput::ChangeEnv => {
-                        debug!("Change env mode");
-                        self.event_tx.send(TUIEvent::RequestEnvChange).unwrap();
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if store_presenter.shows_elapsed() {
+                        render_scheduler.changed();
                     }
-                    _ => {}
                 }
+                Err(RecvTimeoutError::Disconnected) => self.is_running = false,
+            }
+            if store_presenter.store.quit {
+                debug!("Exiting");
+                self.is_running = false;
             }
-            store_presenter.present();
-            store_presenter.update_store();
         }
         Ok(())
     }
 }
 
+/// Whatever may change the screen.
+enum UIUpdate {
+    Store(Box<Store>),
+    Input(Event),
+}
+
+/// Merges the store snapshots and the terminal input into one channel, so the UI can sleep on
+/// both at once.
+fn forward_updates(store_rx: Receiver<Store>) -> Receiver<UIUpdate> {
+    let (updates_tx, updates_rx) = mpsc::channel();
+    let input_tx = updates_tx.clone();
+    thread::spawn(move || {
+        for store in store_rx {
+            if updates_tx.send(UIUpdate::Store(Box::new(store))).is_err() {
+                break;
+            }
+        }
+    });
+    thread::spawn(move || {
+        while let Ok(input) = event::read() {
+            if input_tx.send(UIUpdate::Input(input)).is_err() {
+                break;
+            }
+        }
+    });
+    updates_rx
+}
+
 struct StorePresenter<'a, B>
 where
     B: Backend,
 {
     terminal: &'a mut Terminal<B>,
-    extended_keymap: &'a Vec<fn(KeyCode, &Store, &Sender<TUIEvent>)>,
-    store_rx: &'a Receiver<Store>,
+    extended_keymap: &'a Vec<Keymap>,
+    updates_rx: &'a Receiver<UIUpdate>,
     event_tx: &'a Sender<TUIEvent>,
     action_tx: &'a Sender<TUIAction>,
     store: Store,
@@ -111,39 +306,107 @@ where
 }
 
 impl<'a, B: Backend> StorePresenter<'a, B> {
-    fn init(
+    fn new(
         terminal: &'a mut Terminal<B>,
-        extended_keymap: &'a Vec<fn(KeyCode, &Store, &Sender<TUIEvent>)>,
-        store_rx: &'a Receiver<Store>,
+        extended_keymap: &'a Vec<Keymap>,
+        updates_rx: &'a Receiver<UIUpdate>,
         event_tx: &'a Sender<TUIEvent>,
         action_tx: &'a Sender<TUIAction>,
-    ) -> Result<Self, String> {
-        if let Ok(updated_store) = store_rx.recv() {
-            Ok(StorePresenter {
-                terminal,
-                extended_keymap,
-                store_rx,
-                store: updated_store,
-                event_tx,
-                action_tx,
-                thread_mngt: ThreadManage::new(false, false, false),
-            })
-        } else {
-            Err("nope".to_string())
+        store: Store,
+    ) -> Self {
+        StorePresenter {
+            terminal,
+            extended_keymap,
+            updates_rx,
+            store,
+            event_tx,
+            action_tx,
+            thread_mngt: ThreadManage::new(false, false, false, false, false),
         }
     }
+    /// Whether anything shown counts the time, so it is redrawn every `ELAPSED_REFRESH`.
+    fn shows_elapsed(&self) -> bool {
+        self.store.processes_open
+            || self
+                .store
+                .header_widget
+                .as_ref()
+                .is_some_and(|header| !header.state::<HeaderState>().actions.is_empty())
+    }
+
     fn present(&mut self) {
-        let main_layout = MainLayoutUI::new();
+        let mut main_layout = MainLayoutUI::new();
+        main_layout.split = self.store.split_env.is_some();
         let single_layout = SingleLayoutUI::new();
         let mut ui = UI::main(&main_layout);
         let mut widgets: Vec<Box<&dyn RenderWidget>> = vec![];
         widgets.push(Box::new(self.store.header_widget.as_ref().unwrap()));
+        if self.store.help_open {
+            ui.popup = Some((
+                format!(
+                    "Keys ({} or esc to close)",
+                    self.store.keybindings.keys_for("show-help")
+                ),
+                help_text(&self.store),
+            ));
+        } else if self.store.processes_open {
+            ui.popup = Some((
+                format!(
+                    "Processes ({} or esc to close)",
+                    self.store.keybindings.keys_for("show-processes")
+                ),
+                processes_text(&processes().list()),
+            ));
+        } else if self.store.errors_open {
+            let header = self.store.header_widget.as_ref().unwrap();
+            let header = header.state::<HeaderState>();
+            ui.popup = Some((
+                format!(
+                    "Errors ({} or esc to close, arrows to scroll)",
+                    self.store.keybindings.keys_for("show-errors")
+                ),
+                errors_text(&header.errors),
+            ));
+            ui.popup_scroll = header.errors_scroll;
+        }
         if let Some(login_widget) = &self.store.login_widget {
-            if let Some(Some(_)) = login_widget.get_data().data.get("logs") {
+            if login_widget.get_data().state.lines().is_some() {
                 widgets.push(Box::new(self.store.login_widget.as_ref().unwrap()));
+            } else if self.store.palette_open {
+                widgets.push(Box::new(self.store.palette_widget.as_ref().unwrap()));
+            } else if self.store.logged_in && self.store.namespace_picker_open {
+                widgets.push(Box::new(self.store.namespaces_widget.as_ref().unwrap()));
+            } else if self.store.logged_in
+                && self
+                    .store
+                    .describe_widget
+                    .as_ref()
+                    .unwrap()
+                    .get_data()
+                    .state
+                    .lines()
+                    .is_some()
+            {
+                widgets.push(Box::new(self.store.describe_widget.as_ref().unwrap()));
             } else if self.store.logged_in {
                 widgets.push(Box::new(self.store.pods_widget.as_ref().unwrap()));
                 widgets.push(Box::new(self.store.logs_widget.as_ref().unwrap()));
+                if self.store.split_env.is_some() {
+                    widgets.push(Box::new(self.store.split_pods_widget.as_ref().unwrap()));
+                    widgets.push(Box::new(self.store.split_logs_widget.as_ref().unwrap()));
+                } else {
+                    widgets.push(Box::new(self.store.deployments_widget.as_ref().unwrap()));
+                    widgets.push(Box::new(self.store.events_widget.as_ref().unwrap()));
+                }
+                if let Some(deployment) = &self.store.pending_rollout_undo {
+                    ui.popup = Some((
+                        "Undo rollout".to_string(),
+                        format!(
+                            "\nRoll deployment/{} back to its previous revision?\n\n(y)es / (n)o",
+                            deployment
+                        ),
+                    ));
+                }
             } else if self.store.request_login {
                 ui = UI::single(&single_layout);
                 ui.widget_fn = Some(|f, layout| {
@@ -158,7 +421,7 @@ impl<'a, B: Backend> StorePresenter<'a, B> {
                                 .borders(Borders::all())
                                 .title("It seems I can't reach your resources..."),
                         ),
-                        Self::centered_rect(layout, 50, 30),
+                        centered_rect(layout, 50, 30),
                     )
                 });
             }
@@ -167,106 +430,272 @@ impl<'a, B: Backend> StorePresenter<'a, B> {
         ui.add_to_widgets(widgets);
         self.terminal.draw(|f| ui.ui(f)).unwrap();
     }
-    fn handle_user_input(&self) -> Option<UserInput> {
-        let mut user_input: Option<UserInput> = None;
-        if let Ok(true) = event::poll(Duration::from_millis(10)) {
-            if let Ok(Event::Key(key)) = event::read() {
-                user_input = Self::handle_primary_keys(key.code).or_else(|| {
-                    Self::handle_direction_keys(key.code).or_else(|| {
-                        if self.store.env_change_possible {
-                            match key.code {
-                                KeyCode::Char('1') => {
-                                    self.event_tx
-                                        .send(TUIEvent::EnvChange(KubeEnv::Dev))
-                                        .unwrap();
-                                }
-                                KeyCode::Char('2') => {
-                                    self.event_tx
-                                        .send(TUIEvent::EnvChange(KubeEnv::Prod))
-                                        .unwrap();
-                                }
-                                _ => {}
-                            }
-                        } else if self.store.request_login {
-                            match key.code {
-                                KeyCode::Char('1') => {
-                                    self.event_tx.send(TUIEvent::RequestLoginStop).unwrap();
-                                    self.event_tx.send(TUIEvent::ClearError).unwrap();
-                                    self.event_tx.send(TUIEvent::CheckConnectivity).unwrap();
-                                }
-                                KeyCode::Char('2') => {
-                                    self.event_tx.send(TUIEvent::RequestLoginStop).unwrap();
-                                    self.event_tx.send(TUIEvent::NeedsLogin).unwrap()
-                                }
-                                _ => {
-                                    match key.code {
-                                        KeyCode::Null => {}
-                                        _ => {
-                                            self.event_tx
-                                                .send(TUIEvent::Error(TUIError::KEY(
-                                                    "Unrecognised key: ".to_string()
-                                                        + &format!("{:?}", key.code).to_string()
-                                                        + " Press q to quit",
-                                                )))
-                                                .unwrap();
-                                        }
-                                    };
-                                }
-                            }
-                        } else {
-                            for check in self.extended_keymap {
-                                check(key.code, &self.store, &self.event_tx)
-                            }
-                            match key.code {
-                                KeyCode::Null => {}
-                                _ => {
-                                    self.event_tx
-                                        .send(TUIEvent::Error(TUIError::KEY(
-                                            "Unrecognised key: ".to_string()
-                                                + &format!("{:?}", key.code).to_string()
-                                                + " Press q to quit",
-                                        )))
-                                        .unwrap();
-                                }
-                            };
-                        }
-                        None
-                    })
-                });
+    /// Handles a snapshot or an input, returns whether the screen needs a redraw.
+    fn update(&mut self, update: UIUpdate) -> bool {
+        match update {
+            UIUpdate::Store(store) => {
+                self.update_store(*store);
+                true
             }
+            UIUpdate::Input(Event::Key(key)) => {
+                self.handle_key(key);
+                true
+            }
+            UIUpdate::Input(Event::Mouse(mouse)) => {
+                self.handle_mouse(mouse);
+                // the pointer only moving changes nothing on screen
+                !matches!(mouse.kind, MouseEventKind::Moved)
+            }
+            UIUpdate::Input(Event::Resize(_, _)) => true,
+            UIUpdate::Input(_) => false,
         }
-        user_input
     }
 
-    fn handle_primary_keys(keycode: KeyCode) -> Option<UserInput> {
-        return if let KeyCode::Char('q') = keycode {
-            Some(UserInput::Quit)
-        } else if let KeyCode::Char('E') = keycode {
-            Some(UserInput::ChangeEnv)
+    fn handle_key(&self, key: KeyEvent) {
+        let chord = KeyChord::from(key);
+        let keybindings = &self.store.keybindings;
+        if self.store.help_open {
+            if key.code == KeyCode::Esc
+                || keybindings.action(KeyContext::Global, chord) == Some("show-help")
+            {
+                self.event_tx.send(TUIEvent::ToggleHelp).unwrap();
+            }
+            return;
+        }
+        if self.store.errors_open {
+            let scroll = |lines| {
+                let header = self.store.header_widget.as_ref().unwrap();
+                let text = errors_text(&header.state::<HeaderState>().errors);
+                let end = self
+                    .terminal
+                    .size()
+                    .map_or(u16::MAX, |area| popup_end_scroll(area, &text));
+                self.event_tx
+                    .send(TUIEvent::ScrollErrors(lines, end))
+                    .unwrap()
+            };
+            match key.code {
+                KeyCode::Up => scroll(-1),
+                KeyCode::Down => scroll(1),
+                KeyCode::PageUp => scroll(-10),
+                KeyCode::PageDown => scroll(10),
+                _ if key.code == KeyCode::Esc
+                    || keybindings.action(KeyContext::Global, chord) == Some("show-errors") =>
+                {
+                    self.event_tx.send(TUIEvent::ToggleErrors).unwrap()
+                }
+                _ => {}
+            }
+            return;
+        }
+        if self.store.processes_open {
+            if key.code == KeyCode::Esc
+                || keybindings.action(KeyContext::Global, chord) == Some("show-processes")
+            {
+                self.event_tx.send(TUIEvent::ToggleProcesses).unwrap();
+            }
+            return;
+        }
+        if let Some(describe) = self
+            .store
+            .describe_widget
+            .as_ref()
+            .filter(|describe| describe.get_data().state.lines().is_some())
+        {
+            let lines = match keybindings.action(KeyContext::Global, chord) {
+                Some("focus-down") => 1,
+                Some("focus-up") => -1,
+                _ => 0,
+            };
+            if let (true, Ok(area)) = (lines != 0, self.terminal.size()) {
+                let rect = MainLayoutUI::new().full_rects(area)[0];
+                self.event_tx
+                    .send(TUIEvent::ScrollWidget(
+                        CliWidgetId::DescribePod,
+                        lines,
+                        describe.follow_scroll(rect),
+                    ))
+                    .unwrap();
+                return;
+            }
+        }
+        let log_search_open = self
+            .store
+            .logs_widget
+            .as_ref()
+            .is_some_and(|logs| logs.current_log().search.is_some());
+        if self.store.namespace_picker_open || self.store.palette_open || log_search_open {
+            // keys are typed into the filter, so the bound keys are off
+            self.extended_keymap
+                .iter()
+                .any(|check| check(key.code, &self.store, self.event_tx).is_none());
+            return;
+        }
+        if let Some(action) = keybindings.action(KeyContext::Global, chord) {
+            self.run_command(action);
+        } else if self.store.env_change_possible {
+            if let Some(action) = keybindings.action(KeyContext::EnvPicker, chord) {
+                self.run_command(action);
+            }
+        } else if self.store.request_login {
+            match keybindings.action(KeyContext::Login, chord) {
+                Some("retry") => {
+                    self.event_tx.send(TUIEvent::RequestLoginStop).unwrap();
+                    self.event_tx.send(TUIEvent::ClearError).unwrap();
+                    self.event_tx.send(TUIEvent::CheckConnectivity).unwrap();
+                }
+                Some("login") => {
+                    self.event_tx.send(TUIEvent::RequestLoginStop).unwrap();
+                    self.event_tx.send(TUIEvent::NeedsLogin).unwrap()
+                }
+                _ => self.report_unknown_key(chord),
+            }
         } else {
-            None
-        };
+            let handled = self
+                .extended_keymap
+                .iter()
+                .any(|check| check(key.code, &self.store, self.event_tx).is_none());
+            if !handled {
+                match keybindings.action(KeyContext::Widget, chord) {
+                    Some(action) if self.store.logged_in => self.run_command(action),
+                    _ => self.report_unknown_key(chord),
+                }
+            }
+        }
     }
 
-    fn handle_direction_keys(keycode: KeyCode) -> Option<UserInput> {
-        return if keycode == KeyCode::Char('h') {
-            Some(UserInput::Direction(Direction2::Left))
-        } else if keycode == KeyCode::Char('j') {
-            Some(UserInput::Direction(Direction2::Down))
-        } else if keycode == KeyCode::Char('k') {
-            Some(UserInput::Direction(Direction2::Up))
-        } else if keycode == KeyCode::Char('l') {
-            Some(UserInput::Direction(Direction2::Right))
-        } else {
-            None
+    fn handle_mouse(&self, mouse: MouseEvent) {
+        let overlay_open = self.store.help_open
+            || self.store.processes_open
+            || self.store.errors_open
+            || self.store.palette_open
+            || self.store.namespace_picker_open
+            || self.store.env_change_possible
+            || self.store.pending_rollout_undo.is_some()
+            || self
+                .store
+                .describe_widget
+                .as_ref()
+                .is_some_and(|describe| describe.get_data().state.lines().is_some());
+        if !self.store.logged_in || overlay_open {
+            return;
+        }
+        let Ok(area) = self.terminal.size() else {
+            return;
         };
+        let mut layout = MainLayoutUI::new();
+        layout.split = self.store.split_env.is_some();
+        let (column, row) = (mouse.column, mouse.row);
+        if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+            let env_line = layout.header_rects(1, area)[0];
+            if contains(env_line, column, row) {
+                self.click_env_line(env_line, column);
+                return;
+            }
+        }
+        let rects = layout.body_rects(area);
+        let Some(widget) = self
+            .store
+            .visible_widgets()
+            .into_iter()
+            .find(|widget| contains(rects[widget.get_widget().pos], column, row))
+        else {
+            return;
+        };
+        let rect = rects[widget.get_widget().pos];
+        let id = widget.get_widget().id.clone();
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.event_tx
+                    .send(TUIEvent::FocusWidget(id.clone()))
+                    .unwrap();
+                if matches!(
+                    id,
+                    CliWidgetId::GetPods | CliWidgetId::SplitPods | CliWidgetId::GetDeployments
+                ) && row > rect.y
+                {
+                    // the first line of the rect is its border
+                    let widget = widget.get_widget();
+                    let scroll = widget.selection_scroll(rect).or(widget.scroll);
+                    let line = (row - rect.y - 1 + scroll.unwrap_or(0)) as usize;
+                    self.event_tx.send(TUIEvent::SelectRow(id, line)).unwrap();
+                }
+            }
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown
+                if matches!(
+                    id,
+                    CliWidgetId::GetLogs | CliWidgetId::SplitLogs | CliWidgetId::GetEvents
+                ) =>
+            {
+                let lines = if mouse.kind == MouseEventKind::ScrollUp {
+                    -SCROLL_LINES
+                } else {
+                    SCROLL_LINES
+                };
+                self.event_tx
+                    .send(TUIEvent::ScrollWidget(
+                        id,
+                        lines,
+                        widget.follow_scroll(rect),
+                    ))
+                    .unwrap();
+            }
+            _ => {}
+        }
     }
-    fn update_store(&mut self) {
-        if let Ok(updated_store) = self.store_rx.recv_timeout(Duration::from_millis(20)) {
-            self.store = updated_store
+
+    /// The namespace is shown on the left of the env line and the env on its right.
+    fn click_env_line(&self, env_line: Rect, column: u16) {
+        let header = self
+            .store
+            .header_widget
+            .as_ref()
+            .unwrap()
+            .state::<HeaderState>();
+        let namespace_width =
+            format!("ns: {}", header.namespace.as_deref().unwrap_or_default()).len() as u16;
+        let env_width = header
+            .env
+            .map(|env| format!("{:?}", env).len() as u16)
+            .unwrap_or(0);
+        if column < env_line.x + namespace_width {
+            self.event_tx.send(TUIEvent::OpenNamespacePicker).unwrap();
+        } else if column >= env_line.right().saturating_sub(env_width) {
+            self.event_tx.send(TUIEvent::RequestEnvChange).unwrap();
         }
     }
 
+    fn run_command(&self, id: &str) {
+        debug!("running {}", id);
+        if self
+            .store
+            .header_widget
+            .as_ref()
+            .is_some_and(|header| header.state::<HeaderState>().hint.is_some())
+        {
+            self.event_tx.send(TUIEvent::ClearHint).unwrap();
+        }
+        if let Some(command) = self.store.commands.iter().find(|command| command.id == id) {
+            (command.run)(&self.store, self.event_tx);
+        }
+    }
+
+    fn report_unknown_key(&self, chord: KeyChord) {
+        if chord.code != KeyCode::Null {
+            self.event_tx
+                .send(TUIEvent::ShowHint(format!(
+                    "{} does nothing here, press {} for help",
+                    chord,
+                    self.store.keybindings.keys_for("show-help")
+                )))
+                .unwrap();
+        }
+    }
+
+    fn update_store(&mut self, store: Store) {
+        self.store = store;
+    }
+
     fn initiate_threads(&mut self) {
         if self.store.logged_in {
             if !self.thread_mngt.logs_thread_started {
@@ -290,26 +719,20 @@ impl<'a, B: Backend> StorePresenter<'a, B> {
                 }
                 self.thread_mngt.pods_thread_started = true;
             }
+            if !self.thread_mngt.events_thread_started {
+                debug!("initiate events thread");
+                if let Some(widget_data) = &self.store.events_widget {
+                    widget_data.get_data().initiate_thread.unwrap()(self.action_tx);
+                }
+                self.thread_mngt.events_thread_started = true;
+            }
+            if !self.thread_mngt.deployments_thread_started {
+                debug!("initiate deployments thread");
+                if let Some(widget_data) = &self.store.deployments_widget {
+                    widget_data.get_data().initiate_thread.unwrap()(self.action_tx);
+                }
+                self.thread_mngt.deployments_thread_started = true;
+            }
         }
     }
-
-    fn centered_rect(r: Rect, percent_x: u16, percent_y: u16) -> Rect {
-        let popup_layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Percentage((100 - percent_y) / 2),
-                Constraint::Percentage(percent_y),
-                Constraint::Percentage((100 - percent_y) / 2),
-            ])
-            .split(r);
-
-        Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage((100 - percent_x) / 2),
-                Constraint::Percentage(percent_x),
-                Constraint::Percentage((100 - percent_x) / 2),
-            ])
-            .split(popup_layout[1])[1]
-    }
 }
@@ -0,0 +1,127 @@
+use std::{collections::HashMap, env, fs, path::PathBuf, str::FromStr, sync::OnceLock};
+
+use log::{debug, warn};
+
+/// Settings read from an ini style file:
+///
+/// ```ini
+/// [top]
+/// history = 30
+/// ```
+///
+/// The file is looked up in `$AWS_CLI_CONFIG`, then `$XDG_CONFIG_HOME/aws_cli/config.ini`
+/// and finally `~/.config/aws_cli/config.ini`. Missing files and keys fall back to defaults.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    sections: HashMap<String, HashMap<String, String>>,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// The configuration of this run, loaded on first use.
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::load)
+}
+
+impl Config {
+    pub fn load() -> Self {
+        match config_path().map(|path| (fs::read_to_string(&path), path)) {
+            Some((Ok(text), path)) => {
+                debug!("loading config from {:?}", path);
+                Config::parse(&text)
+            }
+            _ => Config::default(),
+        }
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+        let mut section = String::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.trim().to_string();
+            } else if let Some((key, value)) = line.split_once('=') {
+                sections
+                    .entry(section.clone())
+                    .or_default()
+                    .insert(key.trim().to_string(), unquote(value.trim()).to_string());
+            } else {
+                warn!("ignoring config line {}: {:?}", number + 1, line);
+            }
+        }
+        Config { sections }
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.sections
+            .get(section)
+            .and_then(|values| values.get(key))
+            .map(|value| value.as_str())
+    }
+
+    pub fn sections(&self) -> impl Iterator<Item = (&String, &HashMap<String, String>)> {
+        self.sections.iter()
+    }
+
+    /// Parses `key` of `section`, falling back to `default` when it is missing or invalid.
+    pub fn get_or<T: FromStr>(&self, section: &str, key: &str, default: T) -> T {
+        match self.get(section, key).map(|value| value.parse()) {
+            Some(Ok(value)) => value,
+            Some(Err(_)) => {
+                warn!("invalid value for {}.{} in config", section, key);
+                default
+            }
+            None => default,
+        }
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// Directory for files this tool keeps between runs: `$XDG_DATA_HOME/aws_cli`
+/// or `~/.local/share/aws_cli`.
+pub fn data_dir() -> PathBuf {
+    env::var("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| env::var("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .unwrap_or_default()
+        .join("aws_cli")
+}
+
+/// Directory of the config files: `$XDG_CONFIG_HOME/aws_cli` or `~/.config/aws_cli`.
+pub fn config_dir() -> Option<PathBuf> {
+    env::var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| env::var("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .ok()
+        .map(|dir| dir.join("aws_cli"))
+}
+
+fn config_path() -> Option<PathBuf> {
+    if let Ok(path) = env::var("AWS_CLI_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    config_dir().map(|dir| dir.join("config.ini"))
+}
+
+#[test]
+fn test_parse_config() {
+    let config = Config::parse(
+        "# comment\nglobal = 1\n[top]\nhistory = 40\n interval_secs=5 \nname = \"quoted value\"\nbroken line\n[other]\nhistory = x\n",
+    );
+    assert_eq!(config.get("", "global"), Some("1"));
+    assert_eq!(config.get("top", "name"), Some("quoted value"));
+    assert_eq!(config.get_or("top", "history", 30), 40);
+    assert_eq!(config.get_or("top", "interval_secs", 10), 5);
+    assert_eq!(config.get_or("other", "history", 30), 30);
+    assert_eq!(config.get_or("missing", "history", 30), 30);
+}
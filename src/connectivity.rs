@@ -0,0 +1,148 @@
+use std::{
+    io::ErrorKind,
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use log::debug;
+
+use crate::config::config;
+
+/// How long a command may take, from the `[timeouts]` section of the config.
+///
+/// ```ini
+/// [timeouts]
+/// slow_ms = 1000
+/// default_secs = 60
+/// get_pods_secs = 20
+/// ```
+///
+/// The commands are `update_kubeconfig`, `check_connectivity`, `get_pods`, `get_namespaces`,
+/// `describe_pod`, `get_deployments`, `rollout_undo` and `get_top`; the ones without a
+/// `<command>_secs` of their own take `default_secs`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Timeouts {
+    /// After this long the API server is probed to tell what's wrong, `slow_ms`.
+    pub slow: Duration,
+    /// After this long the command is terminated, `<command>_secs` or else `default_secs`.
+    pub limit: Duration,
+}
+
+impl Timeouts {
+    pub fn new(slow: Duration, limit: Duration) -> Self {
+        Timeouts { slow, limit }
+    }
+
+    pub fn of(command: &str) -> Self {
+        let default = config().get_or("timeouts", "default_secs", 60);
+        Timeouts::new(
+            Duration::from_millis(config().get_or("timeouts", "slow_ms", 1000)),
+            Duration::from_secs(config().get_or("timeouts", &format!("{}_secs", command), default)),
+        )
+    }
+}
+
+/// How long the probe waits for the API server to accept the connection, `probe_ms` in the
+/// `[timeouts]` section of the config.
+pub fn probe_timeout() -> Duration {
+    Duration::from_millis(config().get_or("timeouts", "probe_ms", 2000))
+}
+
+/// Why the API server could not be reached.
+#[derive(Debug, PartialEq)]
+pub enum Unreachable {
+    /// The name did not resolve, private EKS endpoints only do on the VPN.
+    DnsFailure,
+    Refused,
+    TimedOut,
+    Other(String),
+}
+
+impl Unreachable {
+    pub fn describe(&self, host: &str) -> String {
+        match self {
+            Unreachable::DnsFailure => format!("Cannot resolve {}... VPN on ?", host),
+            Unreachable::Refused => format!("{} refused the connection", host),
+            Unreachable::TimedOut => format!("No answer from {}... VPN on ?", host),
+            Unreachable::Other(error) => format!("Cannot reach {}: {}", host, error),
+        }
+    }
+}
+
+/// The host and port of a server url like `https://host[:port][/path]`.
+pub fn endpoint(server: &str) -> Option<(String, u16)> {
+    let (scheme, rest) = server.split_once("://").unwrap_or(("https", server));
+    let authority = rest.split('/').next()?;
+    let default_port = if scheme == "http" { 80 } else { 443 };
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(bracketed) => {
+            let (host, rest) = bracketed.split_once(']')?;
+            (host, rest.strip_prefix(':'))
+        }
+        None => match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+    let port = match port {
+        Some(port) => port.parse().ok()?,
+        None => default_port,
+    };
+    (!host.is_empty()).then(|| (host.to_string(), port))
+}
+
+/// Opens a TCP connection to `host`, telling why it could not be opened.
+pub fn probe(host: &str, port: u16, timeout: Duration) -> Result<(), Unreachable> {
+    let addresses: Vec<_> = match (host, port).to_socket_addrs() {
+        Ok(addresses) => addresses.collect(),
+        Err(error) => {
+            debug!("resolving {} failed: {}", host, error);
+            return Err(Unreachable::DnsFailure);
+        }
+    };
+    let mut result = Err(Unreachable::DnsFailure);
+    for address in addresses {
+        result = match TcpStream::connect_timeout(&address, timeout) {
+            Ok(_) => return Ok(()),
+            Err(error) => {
+                debug!("connecting to {} failed: {}", address, error);
+                Err(match error.kind() {
+                    ErrorKind::ConnectionRefused => Unreachable::Refused,
+                    ErrorKind::TimedOut | ErrorKind::WouldBlock => Unreachable::TimedOut,
+                    _ => Unreachable::Other(error.to_string()),
+                })
+            }
+        };
+    }
+    result
+}
+
+/// What's wrong with the connection to `server`, or `None` when it can be reached.
+pub fn diagnose(server: &str) -> Option<String> {
+    let Some((host, port)) = endpoint(server) else {
+        return Some(format!("No API server in {:?}", server));
+    };
+    probe(&host, port, probe_timeout())
+        .err()
+        .map(|unreachable| unreachable.describe(&host))
+}
+
+#[test]
+fn test_probe_tells_failures_apart() {
+    assert!(
+        endpoint("https://ABC.gr7.eu-west-1.eks.amazonaws.com")
+            == Some(("ABC.gr7.eu-west-1.eks.amazonaws.com".to_string(), 443))
+    );
+    assert!(endpoint("https://127.0.0.1:6443/") == Some(("127.0.0.1".to_string(), 6443)));
+    assert!(endpoint("https://[::1]:6443") == Some(("::1".to_string(), 6443)));
+    assert!(endpoint("https://").is_none());
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let timeout = Duration::from_millis(500);
+    assert!(probe("127.0.0.1", port, timeout) == Ok(()));
+    drop(listener);
+    assert!(probe("127.0.0.1", port, timeout) == Err(Unreachable::Refused));
+    assert!(probe("cluster.invalid", 443, timeout) == Err(Unreachable::DnsFailure));
+    assert!(Unreachable::TimedOut.describe("api") == "No answer from api... VPN on ?");
+}
@@ -0,0 +1,193 @@
+use chrono::{Local, TimeZone};
+
+use crate::{config::config, log_reconnect::auth_expired, structs::TUIError, timestamp::now_secs};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    /// Something may be off, like a slow command or a stream that reconnects.
+    Warning,
+    Error,
+}
+
+/// An error with where and when it happened, as kept in the error history.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ErrorReport {
+    pub error: TUIError,
+    /// The command or action that failed.
+    pub source: String,
+    pub severity: Severity,
+    /// When it happened, in seconds since the epoch.
+    pub time: i64,
+    /// The last lines the command wrote to stderr.
+    pub stderr: Option<String>,
+    /// What might fix it.
+    pub remedy: Option<String>,
+    /// How often it happened in a row, like a stream printing the same error again and again.
+    pub count: usize,
+}
+
+impl ErrorReport {
+    pub fn new(source: impl Into<String>, error: TUIError) -> Self {
+        let severity = match error {
+            TUIError::VPN => Severity::Warning,
+            _ => Severity::Error,
+        };
+        let stderr = match &error {
+            TUIError::API(text) => stderr_excerpt(text),
+            _ => None,
+        };
+        ErrorReport {
+            remedy: remedy(&error).map(str::to_string),
+            error,
+            source: source.into(),
+            severity,
+            time: now_secs(),
+            stderr,
+            count: 1,
+        }
+    }
+
+    /// Whether `other` is this error again.
+    pub fn repeated_by(&self, other: &ErrorReport) -> bool {
+        self.source == other.source && self.error == other.error
+    }
+
+    /// The line shown in the header.
+    pub fn summary(&self) -> String {
+        match &self.error {
+            TUIError::VPN => "Uhm... VPN on ?".to_string(),
+            TUIError::Unreachable(problem) | TUIError::Export(problem) => problem.clone(),
+            TUIError::API(text) => text
+                .lines()
+                .map(str::trim)
+                .rfind(|line| !line.is_empty())
+                .unwrap_or("Unknown error")
+                .to_string(),
+        }
+    }
+}
+
+/// How many errors the history keeps, `history` in the `[errors]` section of the config.
+pub fn history_size() -> usize {
+    config().get_or("errors", "history", 200)
+}
+
+/// The last lines of the output of a command, shortened, `stderr_lines` in the `[errors]` section
+/// of the config.
+fn stderr_excerpt(text: &str) -> Option<String> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty())
+        .collect();
+    let keep = config().get_or("errors", "stderr_lines", 5);
+    let excerpt: Vec<String> = lines[lines.len().saturating_sub(keep)..]
+        .iter()
+        .map(|line| match line.char_indices().nth(200) {
+            Some((end, _)) => format!("{}…", &line[..end]),
+            None => line.to_string(),
+        })
+        .collect();
+    (!excerpt.is_empty()).then(|| excerpt.join("\n"))
+}
+
+fn remedy(error: &TUIError) -> Option<&'static str> {
+    let text = match error {
+        TUIError::VPN => return Some("Turn on the VPN, then retry"),
+        TUIError::Unreachable(problem) if problem.contains("VPN") => {
+            return Some("Turn on the VPN, then retry")
+        }
+        TUIError::Unreachable(_) => {
+            return Some("Check the API server of the cluster in the kubeconfig")
+        }
+        TUIError::Export(_) => return Some("Check the space and permissions of the data dir"),
+        TUIError::API(text) => text.to_lowercase(),
+    };
+    if auth_expired(&text) {
+        Some("Log in to AWS again")
+    } else if text.contains("forbidden") {
+        Some("Your role may not do this here, check the namespace")
+    } else if text.contains("notfound") || text.contains("not found") {
+        Some("It is gone, refresh the view")
+    } else if text.contains("timed out") {
+        Some("Raise the timeout in the [timeouts] section of the config")
+    } else {
+        None
+    }
+}
+
+/// The errors of the history, the newest first, for the error history panel.
+pub fn errors_text(reports: &[ErrorReport]) -> String {
+    if reports.is_empty() {
+        return "No errors so far".to_string();
+    }
+    reports
+        .iter()
+        .rev()
+        .map(|report| {
+            let time = Local
+                .timestamp_opt(report.time, 0)
+                .single()
+                .map(|time| time.format("%H:%M:%S").to_string())
+                .unwrap_or_default();
+            let severity = match report.severity {
+                Severity::Warning => "warning",
+                Severity::Error => "error",
+            };
+            let summary = report.summary();
+            let repeated = match report.count {
+                1 => String::new(),
+                count => format!(" ({} times)", count),
+            };
+            let mut lines = vec![format!(
+                "{} {} in {}: {}{}",
+                time, severity, report.source, summary, repeated
+            )];
+            if let Some(stderr) = report.stderr.as_ref().filter(|stderr| **stderr != summary) {
+                lines.extend(stderr.lines().map(|line| format!("stderr │ {}", line)));
+            }
+            if let Some(remedy) = &report.remedy {
+                lines.push(format!("remedy │ {}", remedy));
+            }
+            lines.join("\n")
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
+#[test]
+fn test_error_reports() {
+    let report = ErrorReport::new(
+        "get pods",
+        TUIError::API(
+            "I1102 10:04:05 request.go:697] Waited\nerror: You must be logged in to the server (Unauthorized)\n"
+                .to_string(),
+        ),
+    );
+    assert!(report.summary() == "error: You must be logged in to the server (Unauthorized)");
+    assert!(report.severity == Severity::Error);
+    assert!(report.remedy == Some("Log in to AWS again".to_string()));
+    assert!(report.stderr.as_ref().unwrap().lines().count() == 2);
+
+    let vpn = ErrorReport::new("check connectivity", TUIError::VPN);
+    assert!(vpn.severity == Severity::Warning && vpn.stderr.is_none());
+    let text = errors_text(&[report, vpn]);
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines[0].ends_with(" warning in check connectivity: Uhm... VPN on ?"));
+    assert!(lines[1] == "remedy │ Turn on the VPN, then retry");
+    assert!(lines[3].ends_with(
+        " error in get pods: error: You must be logged in to the server (Unauthorized)"
+    ));
+    assert!(lines[4] == "stderr │ I1102 10:04:05 request.go:697] Waited");
+    assert!(errors_text(&[]) == "No errors so far");
+
+    let single = ErrorReport::new(
+        "describe api",
+        TUIError::API("pods \"api\" not found".to_string()),
+    );
+    assert!(errors_text(&[single]).ends_with("not found\nremedy │ It is gone, refresh the view"));
+
+    let mut repeated = ErrorReport::new("logs api", TUIError::API("error: EOF".to_string()));
+    repeated.count = 3;
+    assert!(errors_text(&[repeated]).ends_with(" error in logs api: error: EOF (3 times)"));
+}
@@ -0,0 +1,51 @@
+/// Scores how well `pattern` matches `candidate`, or `None` when the characters of the
+/// pattern don't all appear in order. Consecutive and early matches score higher.
+pub fn fuzzy_score(pattern: &str, candidate: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in pattern.to_lowercase().chars() {
+        let found = position + candidate[position..].iter().position(|c| *c == wanted)?;
+        score += match previous {
+            Some(previous) if previous + 1 == found => 10,
+            Some(_) => 1,
+            None => 5 - found.min(5) as i64,
+        };
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+/// Keeps the candidates matching `pattern`, best match first. Equal scores keep their order.
+pub fn fuzzy_filter<'a>(pattern: &str, candidates: &'a [String]) -> Vec<&'a String> {
+    let mut matches: Vec<(i64, &String)> = candidates
+        .iter()
+        .filter_map(|candidate| fuzzy_score(pattern, candidate).map(|score| (score, candidate)))
+        .collect();
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    matches
+        .into_iter()
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+#[test]
+fn test_fuzzy_filter() {
+    let candidates: Vec<String> = [
+        "kube-system",
+        "myccv-dev-salespoint",
+        "myccv-dev-sales",
+        "monitoring",
+    ]
+    .iter()
+    .map(|name| name.to_string())
+    .collect();
+    assert!(fuzzy_score("", "anything") == Some(0));
+    assert!(fuzzy_score("xyz", "kube-system").is_none());
+    assert!(fuzzy_filter("", &candidates).len() == 4);
+    assert!(fuzzy_filter("sales", &candidates) == vec![&candidates[1], &candidates[2]]);
+    assert!(fuzzy_filter("SYS", &candidates) == vec![&candidates[0]]);
+    assert!(fuzzy_filter("mon", &candidates)[0] == &candidates[3]);
+}
@@ -0,0 +1,381 @@
+use std::{collections::HashMap, fmt, fs, path::PathBuf, str::FromStr};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use log::debug;
+
+use crate::config::{config, config_dir, Config};
+
+/// A key together with the modifiers held down, written like `ctrl+p` or `E` in the keys file.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // shift is already part of the character, `E` arrives as shift+E
+        let modifiers = match code {
+            KeyCode::Char(_) => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        KeyChord { code, modifiers }
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(key: KeyEvent) -> Self {
+        KeyChord::new(key.code, key.modifiers)
+    }
+}
+
+const KEY_NAMES: [(&str, KeyCode); 13] = [
+    ("esc", KeyCode::Esc),
+    ("enter", KeyCode::Enter),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("space", KeyCode::Char(' ')),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = text.trim().split('+').collect();
+        let key = parts.pop().unwrap_or_default();
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in parts {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier {:?} in {:?}", modifier, text)),
+            };
+        }
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => KEY_NAMES
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, code)| *code)
+                .or_else(|| {
+                    key.to_lowercase()
+                        .strip_prefix('f')
+                        .and_then(|number| number.parse().ok())
+                        .map(KeyCode::F)
+                })
+                .ok_or_else(|| format!("unknown key {:?}", text))?,
+        };
+        Ok(KeyChord::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl+"),
+            (KeyModifiers::ALT, "alt+"),
+            (KeyModifiers::SHIFT, "shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}", name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(number) => write!(f, "f{}", number),
+            code => match KEY_NAMES.iter().find(|(_, known)| *known == code) {
+                Some((name, _)) => write!(f, "{}", name),
+                None => write!(f, "{:?}", code),
+            },
+        }
+    }
+}
+
+/// Where a key is pressed. A binding only applies in its own context, keys of the global
+/// context work everywhere except in the pickers that keys are typed into.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum KeyContext {
+    Global,
+    /// The "can't reach your resources" prompt.
+    Login,
+    /// After `change-env`, waiting for the env to switch to.
+    EnvPicker,
+    /// Keys handled by the widgets once logged in.
+    Widget,
+}
+
+impl KeyContext {
+    pub const ALL: [KeyContext; 4] = [
+        KeyContext::Global,
+        KeyContext::Login,
+        KeyContext::EnvPicker,
+        KeyContext::Widget,
+    ];
+
+    /// Section of the keys file holding the bindings of this context.
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyContext::Global => "global",
+            KeyContext::Login => "login",
+            KeyContext::EnvPicker => "env_picker",
+            KeyContext::Widget => "widget",
+        }
+    }
+}
+
+/// Actions of the login prompt with their description, the other contexts run palette commands.
+pub const LOGIN_ACTIONS: [(&str, &str); 2] = [
+    ("retry", "Retry, I forgot to turn on my VPN"),
+    ("login", "Log in to AWS"),
+];
+
+const DEFAULT_BINDINGS: [(KeyContext, &str, &str); 26] = [
+    (KeyContext::Global, "quit", "q"),
+    (KeyContext::Global, "change-env", "E"),
+    (KeyContext::Global, "focus-left", "h"),
+    (KeyContext::Global, "focus-down", "j"),
+    (KeyContext::Global, "focus-up", "k"),
+    (KeyContext::Global, "focus-right", "l"),
+    (KeyContext::Global, "command-palette", ":, ctrl+p"),
+    (KeyContext::Global, "show-help", "?"),
+    (KeyContext::Global, "show-processes", "P"),
+    (KeyContext::Global, "show-errors", "!"),
+    (KeyContext::Global, "next-action", "A"),
+    (KeyContext::Global, "cancel-action", "C"),
+    (KeyContext::Login, "retry", "1"),
+    (KeyContext::Login, "login", "2"),
+    (KeyContext::EnvPicker, "switch-to-dev", "1"),
+    (KeyContext::EnvPicker, "switch-to-prod", "2"),
+    (KeyContext::Widget, "describe-pod", "d"),
+    (KeyContext::Widget, "filter-events", "f"),
+    (KeyContext::Widget, "undo-rollout", "u"),
+    (KeyContext::Widget, "toggle-split", "S"),
+    (KeyContext::Widget, "switch-namespace", "n"),
+    (KeyContext::Widget, "next-log-tab", "]"),
+    (KeyContext::Widget, "previous-log-tab", "["),
+    (KeyContext::Widget, "search-logs", "/"),
+    (KeyContext::Widget, "export-logs", "x"),
+    (KeyContext::Widget, "cycle-log-time", "t"),
+];
+
+/// Maps key chords to the names of actions, per context.
+///
+/// The defaults can be changed in an ini style keys file, one section per context:
+///
+/// ```ini
+/// [global]
+/// quit = ctrl+c, q
+/// [widget]
+/// describe-pod = enter
+/// ```
+///
+/// Listing an action replaces all of its default keys in that context, an empty value unbinds
+/// it. The file is `keys.ini` next to the config, or the `file` of the `[keys]` config section.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Keybindings {
+    maps: HashMap<KeyContext, HashMap<KeyChord, String>>,
+}
+
+impl Keybindings {
+    /// Loads the keys file and checks the bindings against the actions that exist.
+    pub fn load(commands: &[&str]) -> Result<Self, String> {
+        match keys_path().map(|path| (fs::read_to_string(&path), path)) {
+            Some((Ok(text), path)) => {
+                debug!("loading key bindings from {:?}", path);
+                Keybindings::parse(&text, commands)
+                    .map_err(|error| format!("{:?}: {}", path, error))
+            }
+            _ => Keybindings::parse("", commands),
+        }
+    }
+
+    /// The default bindings with the ones of `text` applied on top. Fails on unknown contexts,
+    /// actions or keys, and on keys bound twice.
+    pub fn parse(text: &str, commands: &[&str]) -> Result<Self, String> {
+        let mut actions: HashMap<(KeyContext, String), Vec<KeyChord>> = HashMap::new();
+        for (context, action, keys) in DEFAULT_BINDINGS {
+            actions.insert((context, action.to_string()), parse_chords(keys)?);
+        }
+        for (section, bindings) in Config::parse(text).sections() {
+            let context = KeyContext::ALL
+                .into_iter()
+                .find(|context| context.name() == section)
+                .ok_or_else(|| format!("unknown key context [{}]", section))?;
+            for (action, keys) in bindings {
+                let known = match context {
+                    KeyContext::Login => LOGIN_ACTIONS.iter().any(|(id, _)| id == action),
+                    _ => commands.contains(&action.as_str()),
+                };
+                if !known {
+                    return Err(format!("unknown action {:?} in [{}]", action, section));
+                }
+                actions.insert((context, action.clone()), parse_chords(keys)?);
+            }
+        }
+
+        let mut maps: HashMap<KeyContext, HashMap<KeyChord, String>> = HashMap::new();
+        let mut sorted: Vec<_> = actions.into_iter().collect();
+        sorted.sort_by(|((_, a), _), ((_, b), _)| a.cmp(b));
+        for ((context, action), chords) in sorted {
+            for chord in chords {
+                let map = maps.entry(context).or_default();
+                if let Some(other) = map.insert(chord, action.clone()) {
+                    return Err(format!(
+                        "{} is bound to both {} and {} in [{}]",
+                        chord,
+                        other,
+                        action,
+                        context.name()
+                    ));
+                }
+            }
+        }
+        // the global keys are looked at first, they would hide the keys of the other contexts
+        let global = maps.get(&KeyContext::Global).cloned().unwrap_or_default();
+        for (context, map) in &maps {
+            if *context == KeyContext::Global {
+                continue;
+            }
+            for (chord, action) in map {
+                if let Some(other) = global.get(chord) {
+                    return Err(format!(
+                        "{} of {} in [{}] is already bound to {} in [global]",
+                        chord,
+                        action,
+                        context.name(),
+                        other
+                    ));
+                }
+            }
+        }
+        Ok(Keybindings { maps })
+    }
+
+    /// The action bound to `chord` in `context`.
+    pub fn action(&self, context: KeyContext, chord: KeyChord) -> Option<&str> {
+        self.maps
+            .get(&context)
+            .and_then(|map| map.get(&chord))
+            .map(|action| action.as_str())
+    }
+
+    /// The actions bound in `context` with their keys, by action name.
+    pub fn actions(&self, context: KeyContext) -> Vec<(&str, String)> {
+        let mut actions: Vec<&str> = self
+            .maps
+            .get(&context)
+            .map(|map| map.values().map(|action| action.as_str()).collect())
+            .unwrap_or_default();
+        actions.sort();
+        actions.dedup();
+        actions
+            .into_iter()
+            .map(|action| (action, self.keys_in(context, action)))
+            .collect()
+    }
+
+    fn keys_in(&self, context: KeyContext, action: &str) -> String {
+        let mut keys: Vec<String> = self
+            .maps
+            .get(&context)
+            .into_iter()
+            .flat_map(|map| map.iter())
+            .filter(|(_, bound)| *bound == action)
+            .map(|(chord, _)| chord.to_string())
+            .collect();
+        keys.sort();
+        keys.join(", ")
+    }
+
+    /// The keys bound to `action` in any context, like `:, ctrl+p`.
+    pub fn keys_for(&self, action: &str) -> String {
+        let mut keys: Vec<String> = self
+            .maps
+            .values()
+            .flat_map(|map| map.iter())
+            .filter(|(_, bound)| *bound == action)
+            .map(|(chord, _)| chord.to_string())
+            .collect();
+        keys.sort();
+        keys.join(", ")
+    }
+}
+
+fn parse_chords(keys: &str) -> Result<Vec<KeyChord>, String> {
+    keys.split(',')
+        .filter(|key| !key.trim().is_empty())
+        .map(|key| key.parse())
+        .collect()
+}
+
+fn keys_path() -> Option<PathBuf> {
+    if let Some(path) = config().get("keys", "file") {
+        return Some(PathBuf::from(path));
+    }
+    config_dir().map(|dir| dir.join("keys.ini"))
+}
+
+#[test]
+fn test_parse_key_chords() {
+    let chord = |text: &str| text.parse::<KeyChord>().unwrap();
+    assert!(chord("q") == KeyChord::new(KeyCode::Char('q'), KeyModifiers::NONE));
+    assert!(chord("Ctrl+P") == KeyChord::new(KeyCode::Char('P'), KeyModifiers::CONTROL));
+    assert!(chord("alt+enter") == KeyChord::new(KeyCode::Enter, KeyModifiers::ALT));
+    assert!(chord("f5") == KeyChord::new(KeyCode::F(5), KeyModifiers::NONE));
+    assert!(chord("E") == KeyChord::new(KeyCode::Char('E'), KeyModifiers::SHIFT));
+    assert!(chord("ctrl+shift+tab").to_string() == "ctrl+shift+tab");
+    assert!("hyper+q".parse::<KeyChord>().is_err());
+    assert!("escape".parse::<KeyChord>().is_err());
+}
+
+#[test]
+fn test_parse_keybindings() {
+    let commands = ["quit", "change-env", "describe-pod", "switch-to-dev"];
+    let chord = |text: &str| text.parse::<KeyChord>().unwrap();
+
+    let defaults = Keybindings::parse("", &commands).unwrap();
+    assert!(defaults.action(KeyContext::Global, chord("q")) == Some("quit"));
+    assert!(defaults.action(KeyContext::Login, chord("1")) == Some("retry"));
+    assert!(defaults.action(KeyContext::EnvPicker, chord("1")) == Some("switch-to-dev"));
+    assert!(defaults.keys_for("command-palette") == ":, ctrl+p");
+    assert!(
+        defaults.actions(KeyContext::EnvPicker)
+            == vec![
+                ("switch-to-dev", "1".to_string()),
+                ("switch-to-prod", "2".to_string())
+            ]
+    );
+
+    let custom = Keybindings::parse(
+        "[global]\nquit = ctrl+c, Q\n[widget]\ndescribe-pod = enter\n",
+        &commands,
+    )
+    .unwrap();
+    assert!(custom.action(KeyContext::Global, chord("q")).is_none());
+    assert!(custom.action(KeyContext::Global, chord("ctrl+c")) == Some("quit"));
+    assert!(custom.action(KeyContext::Widget, chord("enter")) == Some("describe-pod"));
+    assert!(custom.action(KeyContext::Widget, chord("d")).is_none());
+
+    assert!(Keybindings::parse("[global]\nquit = E\n", &commands)
+        .unwrap_err()
+        .contains("bound to both change-env and quit"));
+    assert!(
+        Keybindings::parse("[widget]\ndescribe-pod = q\n", &commands)
+            .unwrap_err()
+            .contains("already bound to quit in [global]")
+    );
+    assert!(Keybindings::parse("[global]\nfly = x\n", &commands).is_err());
+    assert!(Keybindings::parse("[nowhere]\nquit = x\n", &commands).is_err());
+    assert!(Keybindings::parse("[login]\nretry = r\n", &commands).is_ok());
+}
@@ -0,0 +1,34 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use log::{debug, warn};
+
+use crate::config::{config, data_dir};
+
+static KUBECONFIG: OnceLock<PathBuf> = OnceLock::new();
+
+/// The kubeconfig owned by this tool, so switching env never touches the current context
+/// of `~/.kube/config`. It lives in the data dir unless `kubeconfig` is set in the `[kube]`
+/// section of the config.
+pub fn kubeconfig() -> &'static Path {
+    KUBECONFIG.get_or_init(|| {
+        let path = kubeconfig_path();
+        if let Some(dir) = path.parent() {
+            if let Err(error) = fs::create_dir_all(dir) {
+                warn!("could not create {:?}: {}", dir, error);
+            }
+        }
+        debug!("using kubeconfig {:?}", path);
+        path
+    })
+}
+
+fn kubeconfig_path() -> PathBuf {
+    if let Some(path) = config().get("kube", "kubeconfig") {
+        return PathBuf::from(path);
+    }
+    data_dir().join("kubeconfig")
+}
@@ -0,0 +1,238 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Once,
+    },
+};
+
+use log::warn;
+
+use crate::config::{config, data_dir};
+
+/// Lines per segment file.
+const SEGMENT_LINES: usize = 10_000;
+
+/// Lines a log dropped from memory, appended to segment files so they can still be scrolled
+/// back to, searched and exported. The files are removed with the history.
+#[derive(Debug)]
+pub struct LogHistory {
+    dir: PathBuf,
+    /// Lines in each segment, the oldest segment first.
+    segments: Vec<usize>,
+    /// Appends to the last segment.
+    writer: Option<BufWriter<File>>,
+}
+
+impl LogHistory {
+    pub fn create(dir: PathBuf) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(LogHistory {
+            dir,
+            segments: vec![],
+            writer: None,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.segments.iter().sum()
+    }
+
+    fn segment_path(&self, segment: usize) -> PathBuf {
+        self.dir.join(format!("{:06}.log", segment))
+    }
+
+    pub fn append<'a>(&mut self, lines: impl IntoIterator<Item = &'a str>) -> io::Result<()> {
+        for line in lines {
+            if self.segments.last().is_none_or(|&len| len >= SEGMENT_LINES) {
+                let file = File::create(self.segment_path(self.segments.len()))?;
+                self.writer = Some(BufWriter::new(file));
+                self.segments.push(0);
+            }
+            let writer = self.writer.as_mut().unwrap();
+            writer.write_all(line.strip_suffix('\n').unwrap_or(line).as_bytes())?;
+            writer.write_all(b"\n")?;
+            *self.segments.last_mut().unwrap() += 1;
+        }
+        Ok(())
+    }
+
+    /// The lines of `range`, each ended by a new line like the lines of a log.
+    pub fn read(&mut self, range: Range<usize>) -> io::Result<Vec<String>> {
+        self.flush()?;
+        let mut lines = Vec::with_capacity(range.len());
+        let mut start = 0;
+        for (segment, &len) in self.segments.iter().enumerate() {
+            let end = start + len;
+            if end > range.start && start < range.end {
+                let skip = range.start.saturating_sub(start);
+                let take = range.end.min(end) - start - skip;
+                for line in self
+                    .segment_lines(segment)?
+                    .into_iter()
+                    .skip(skip)
+                    .take(take)
+                {
+                    lines.push(line + "\n");
+                }
+            }
+            start = end;
+        }
+        Ok(lines)
+    }
+
+    /// Index of the last line before `before` containing `query`.
+    pub fn rfind(&mut self, query: &str, before: usize) -> io::Result<Option<usize>> {
+        self.flush()?;
+        let mut end = self.len();
+        for (segment, &len) in self.segments.iter().enumerate().rev() {
+            let start = end - len;
+            if start < before {
+                let found = self
+                    .segment_lines(segment)?
+                    .iter()
+                    .take(before - start)
+                    .rposition(|line| line.contains(query));
+                if let Some(found) = found {
+                    return Ok(Some(start + found));
+                }
+            }
+            end = start;
+        }
+        Ok(None)
+    }
+
+    /// Writes the oldest `lines` lines to `out`, the oldest first.
+    pub fn export(&mut self, out: &mut impl Write, lines: usize) -> io::Result<()> {
+        self.flush()?;
+        let mut left = lines;
+        for (segment, &len) in self.segments.iter().enumerate() {
+            let mut file = BufReader::new(File::open(self.segment_path(segment))?);
+            if len <= left {
+                io::copy(&mut file, out)?;
+            } else {
+                for line in file.lines().take(left) {
+                    writeln!(out, "{}", line?)?;
+                }
+            }
+            left = left.saturating_sub(len);
+        }
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.writer = None;
+        for segment in 0..self.segments.len() {
+            let _ = fs::remove_file(self.segment_path(segment));
+        }
+        self.segments.clear();
+    }
+
+    fn segment_lines(&self, segment: usize) -> io::Result<Vec<String>> {
+        BufReader::new(File::open(self.segment_path(segment))?)
+            .lines()
+            .collect()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.writer {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for LogHistory {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+static HISTORIES: AtomicUsize = AtomicUsize::new(0);
+static REMOVE_STALE: Once = Once::new();
+
+/// A new history for a log widget of this run, unless `spill` of its `[truncate.<widget>]` config
+/// section turns it off.
+pub fn widget_history(widget: &str, spill: bool) -> Option<LogHistory> {
+    if !config().get_or(&format!("truncate.{}", widget), "spill", spill) {
+        return None;
+    }
+    REMOVE_STALE.call_once(|| remove_stale(&data_dir().join("history")));
+    let dir = data_dir().join("history").join(format!(
+        "{}-{}-{}",
+        widget,
+        process::id(),
+        HISTORIES.fetch_add(1, Ordering::Relaxed)
+    ));
+    LogHistory::create(dir)
+        .inspect_err(|error| warn!("keeping no history of {}: {}", widget, error))
+        .ok()
+}
+
+/// Removes the histories of runs that crashed before removing them, named `<widget>-<pid>-<n>`
+/// after a process that is gone.
+fn remove_stale(histories: &Path) {
+    let Ok(entries) = fs::read_dir(histories) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let pid = name.rsplit('-').nth(1).and_then(|pid| pid.parse().ok());
+        if pid.is_some_and(|pid| !is_running(pid)) {
+            if let Err(error) = fs::remove_dir_all(entry.path()) {
+                warn!("could not remove the stale history {}: {}", name, error);
+            }
+        }
+    }
+}
+
+fn is_running(pid: libc::pid_t) -> bool {
+    // SAFETY: signal 0 only checks whether the process exists
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[test]
+fn test_log_history_spans_segments() {
+    let dir = std::env::temp_dir().join(format!("aws_cli-history-test-{}", process::id()));
+    let mut history = LogHistory::create(dir.clone()).unwrap();
+    let lines: Vec<String> = (0..25_000).map(|line| format!("line {}\n", line)).collect();
+    history
+        .append(lines.iter().map(|line| line.as_str()))
+        .unwrap();
+    assert!(history.segments == vec![10_000, 10_000, 5_000]);
+    assert!(history.read(9_998..10_002).unwrap() == lines[9_998..10_002]);
+    assert!(history.read(24_999..30_000).unwrap() == lines[24_999..]);
+    assert!(history.rfind("line 1", 20_000).unwrap() == Some(19_999));
+    assert!(history.rfind("line 12345", 12_345).unwrap().is_none());
+    assert!(history.rfind("line 12345", 12_346).unwrap() == Some(12_345));
+    let mut exported = vec![];
+    history.export(&mut exported, history.len()).unwrap();
+    assert!(String::from_utf8(exported).unwrap() == lines.concat());
+    let mut exported = vec![];
+    history.export(&mut exported, 10_002).unwrap();
+    assert!(String::from_utf8(exported).unwrap() == lines[..10_002].concat());
+    history.clear();
+    assert!(history.len() == 0 && history.read(0..10).unwrap().is_empty());
+    drop(history);
+    assert!(!dir.exists());
+}
+
+#[test]
+fn test_remove_stale_histories() {
+    let histories =
+        std::env::temp_dir().join(format!("aws_cli-stale-history-test-{}", process::id()));
+    let running = histories.join(format!("logs-{}-0", process::id()));
+    // pids are below the largest pid_t, so this one is gone
+    let gone = histories.join(format!("logs-{}-3", libc::pid_t::MAX));
+    for dir in [&running, &gone] {
+        fs::create_dir_all(dir).unwrap();
+    }
+    remove_stale(&histories);
+    assert!(running.exists() && !gone.exists());
+    fs::remove_dir_all(histories).unwrap();
+}
@@ -0,0 +1,253 @@
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    ops::Range,
+    sync::mpsc::{Receiver, RecvTimeoutError, Sender},
+    time::{Duration, Instant},
+};
+
+use chrono::{Local, TimeZone};
+
+use crate::{
+    config::config,
+    structs::TUIEvent,
+    timestamp::{human_age, now_millis, parse_rfc3339_millis},
+};
+
+/// Whether the logs are followed with `--timestamps` and merged in time order, from `timestamps`
+/// in the `[logs]` section of the config.
+pub fn timestamps_enabled() -> bool {
+    config().get_or("logs", "timestamps", false)
+}
+
+/// The line without the timestamp added by `--timestamps`, which is asked for anyway to resume
+/// streams when `timestamps` is off.
+pub fn strip_timestamp(line: String) -> String {
+    match line_timestamp(&line) {
+        Some((range, _)) => {
+            let end = line[range.end..]
+                .strip_prefix(' ')
+                .map_or(range.end, |_| range.end + 1);
+            format!("{}{}", &line[..range.start], &line[end..])
+        }
+        None => line,
+    }
+}
+
+/// How long log lines are held back to be put in order, `reorder_ms` in `[logs]`.
+pub fn reorder_window() -> Duration {
+    Duration::from_millis(config().get_or("logs", "reorder_ms", 500))
+}
+
+/// Where the timestamp added by `--timestamps` is in a log line and when the line was logged, in
+/// milliseconds since the epoch. The timestamp follows the `[...] ` prefixes of `--prefix` and of
+/// the "all" tab.
+pub fn line_timestamp(line: &str) -> Option<(Range<usize>, i64)> {
+    let mut start = 0;
+    while line[start..].starts_with('[') {
+        start += line[start..].find("] ")? + 2;
+    }
+    let end = line[start..]
+        .find(char::is_whitespace)
+        .map_or(line.len(), |end| start + end);
+    let millis = parse_rfc3339_millis(&line[start..end])?;
+    Some((start..end, millis))
+}
+
+/// Holds log lines back for a window after they arrived and releases them in the order they were
+/// logged, so the lines of pods and sources read through separate pipes interleave by time.
+#[derive(Debug)]
+pub struct ReorderWindow {
+    window: Duration,
+    /// Lines held back by timestamp, then arrival, the oldest first.
+    held: BinaryHeap<Reverse<(i64, u64, usize, String)>>,
+    /// When the held lines arrived and their timestamps, in arrival order.
+    arrivals: VecDeque<(Instant, i64)>,
+    /// Timestamp of the last line of each source, given to its lines without one.
+    last: HashMap<usize, i64>,
+    arrived: u64,
+}
+
+impl ReorderWindow {
+    pub fn new(window: Duration) -> Self {
+        ReorderWindow {
+            window,
+            held: BinaryHeap::new(),
+            arrivals: VecDeque::new(),
+            last: HashMap::new(),
+            arrived: 0,
+        }
+    }
+
+    pub fn push(&mut self, source: usize, line: String, now: Instant) {
+        let millis = line_timestamp(&line)
+            .map(|(_, millis)| millis)
+            .or_else(|| self.last.get(&source).copied())
+            .unwrap_or(i64::MIN);
+        self.last.insert(source, millis);
+        self.held
+            .push(Reverse((millis, self.arrived, source, line)));
+        self.arrived += 1;
+        self.arrivals.push_back((now, millis));
+    }
+
+    /// The lines held for the whole window and every line logged before them, in time order.
+    pub fn release(&mut self, now: Instant) -> Vec<(usize, String)> {
+        let mut until = None;
+        while let Some(&(arrival, millis)) = self.arrivals.front() {
+            if arrival + self.window > now {
+                break;
+            }
+            until = until.max(Some(millis));
+            self.arrivals.pop_front();
+        }
+        let mut released = vec![];
+        if let Some(until) = until {
+            while self
+                .held
+                .peek()
+                .is_some_and(|Reverse((millis, ..))| *millis <= until)
+            {
+                let Reverse((_, _, source, line)) = self.held.pop().unwrap();
+                released.push((source, line));
+            }
+        }
+        released
+    }
+
+    /// Time until the next line is due, `None` when no line is held.
+    pub fn next_due(&self, now: Instant) -> Option<Duration> {
+        self.arrivals
+            .front()
+            .map(|(arrival, _)| (*arrival + self.window).saturating_duration_since(now))
+    }
+}
+
+/// Forwards the events of the log streams to `event_tx`, putting the log lines in time order
+/// through a `ReorderWindow`.
+pub fn merge_logs(log_rx: Receiver<TUIEvent>, event_tx: Sender<TUIEvent>, window: Duration) {
+    let mut reorder = ReorderWindow::new(window);
+    loop {
+        let event = match reorder.next_due(Instant::now()) {
+            Some(due) => log_rx.recv_timeout(due),
+            None => log_rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match event {
+            Ok(TUIEvent::AddLog(source, line)) => reorder.push(source, line, Instant::now()),
+            Ok(event) => {
+                if event_tx.send(event).is_err() {
+                    return;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        for (source, line) in reorder.release(Instant::now()) {
+            if event_tx.send(TUIEvent::AddLog(source, line)).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// How the timestamps of the log lines are shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TimeColumn {
+    /// As kubectl prints them.
+    Raw,
+    /// In the local time zone, to the millisecond.
+    #[default]
+    Local,
+    /// As the time since the line was logged.
+    Relative,
+}
+
+impl TimeColumn {
+    /// From `time_column` in `[logs]`: `raw`, `local` or `relative`.
+    pub fn from_config() -> Self {
+        match config().get("logs", "time_column") {
+            Some("raw") => TimeColumn::Raw,
+            Some("relative") => TimeColumn::Relative,
+            _ => TimeColumn::Local,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            TimeColumn::Raw => TimeColumn::Local,
+            TimeColumn::Local => TimeColumn::Relative,
+            TimeColumn::Relative => TimeColumn::Raw,
+        }
+    }
+
+    /// The line with its timestamp shown this way.
+    pub fn show(self, line: &str) -> Cow<'_, str> {
+        let Some((range, millis)) = line_timestamp(line).filter(|_| self != TimeColumn::Raw) else {
+            return Cow::Borrowed(line);
+        };
+        let time = match self {
+            TimeColumn::Relative => {
+                format!("{} ago", human_age((now_millis() - millis) / 1000))
+            }
+            _ => match Local.timestamp_millis_opt(millis).single() {
+                Some(time) => time.format("%H:%M:%S%.3f").to_string(),
+                None => return Cow::Borrowed(line),
+            },
+        };
+        Cow::Owned(format!(
+            "{}{}{}",
+            &line[..range.start],
+            time,
+            &line[range.end..]
+        ))
+    }
+}
+
+#[test]
+fn test_reorder_window_releases_lines_in_time_order() {
+    let start = Instant::now();
+    let window = Duration::from_millis(500);
+    let mut reorder = ReorderWindow::new(window);
+    reorder.push(0, "[pod/a/c] 2023-11-02T10:04:05.300Z third".into(), start);
+    reorder.push(1, "[pod/b/c] 2023-11-02T10:04:05.100Z first".into(), start);
+    reorder.push(1, "  continued".into(), start);
+    reorder.push(0, "[pod/a/c] 2023-11-02T10:04:05.200Z second".into(), start);
+    assert!(reorder.release(start).is_empty());
+    assert!(reorder.next_due(start) == Some(window));
+
+    let later = start + Duration::from_millis(400);
+    reorder.push(1, "[pod/b/c] 2023-11-02T10:04:05.250Z late".into(), later);
+    reorder.push(1, "[pod/b/c] 2023-11-02T10:04:06.000Z newer".into(), later);
+    let released: Vec<String> = reorder
+        .release(start + window)
+        .into_iter()
+        .map(|(_, line)| line)
+        .collect();
+    assert!(
+        released
+            == [
+                "[pod/b/c] 2023-11-02T10:04:05.100Z first",
+                "  continued",
+                "[pod/a/c] 2023-11-02T10:04:05.200Z second",
+                "[pod/b/c] 2023-11-02T10:04:05.250Z late",
+                "[pod/a/c] 2023-11-02T10:04:05.300Z third",
+            ]
+    );
+    assert!(reorder.release(later + window).len() == 1);
+    assert!(reorder.next_due(later + window).is_none());
+}
+
+#[test]
+fn test_time_column_replaces_the_timestamp() {
+    let line = "[api] [pod/a/c] 2023-11-02T10:04:05.300Z started";
+    assert!(line_timestamp(line).unwrap() == (16..40, 1698919445300));
+    assert!(line_timestamp("[pod/a/c] started").is_none());
+    assert!(TimeColumn::Raw.show(line) == line);
+    let relative = TimeColumn::Relative.show(line);
+    assert!(relative.starts_with("[api] [pod/a/c] ") && relative.ends_with("d ago started"));
+    assert!(TimeColumn::Local.show(line).ends_with(":05.300 started"));
+    assert!(TimeColumn::Local.show("no timestamp") == "no timestamp");
+    assert!(strip_timestamp(line.to_string()) == "[api] [pod/a/c] started");
+    assert!(strip_timestamp("[pod/a/c] started".to_string()) == "[pod/a/c] started");
+}
@@ -0,0 +1,169 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::{config::config, log_merge::line_timestamp};
+
+/// Waits between attempts to reconnect a stream, doubling up to a maximum.
+#[derive(Debug)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    attempt: u32,
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Backoff {
+            initial,
+            max,
+            attempt: 0,
+        }
+    }
+
+    /// From `reconnect_initial_ms` and `reconnect_max_secs` in the `[logs]` section of the config.
+    pub fn from_config() -> Self {
+        Backoff::new(
+            Duration::from_millis(config().get_or("logs", "reconnect_initial_ms", 1000)),
+            Duration::from_secs(config().get_or("logs", "reconnect_max_secs", 60)),
+        )
+    }
+
+    /// Attempts since the stream last worked.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// The wait before the next attempt.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self
+            .initial
+            .saturating_mul(2u32.saturating_pow(self.attempt))
+            .min(self.max);
+        self.attempt += 1;
+        delay
+    }
+
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+/// How far a followed log stream got, to resume it after a reconnect without a gap or repeated
+/// lines.
+#[derive(Debug)]
+pub struct StreamResume {
+    /// Per pod, from the `[pod/...]` prefix: the newest timestamp, as printed and in milliseconds,
+    /// and the lines logged at that millisecond, which a resumed stream sends again.
+    pods: HashMap<String, (String, i64, Vec<String>)>,
+    /// When the last line, or the first connection when none came yet, was received.
+    received: Instant,
+}
+
+impl StreamResume {
+    pub fn new(now: Instant) -> Self {
+        StreamResume {
+            pods: HashMap::new(),
+            received: now,
+        }
+    }
+
+    /// Whether the line was not received before, noting it when it was not.
+    pub fn keep(&mut self, line: &str, now: Instant) -> bool {
+        self.received = now;
+        let Some((range, millis)) = line_timestamp(line) else {
+            return true;
+        };
+        let pod = line[..range.start].to_string();
+        let time = line[range.clone()].to_string();
+        match self.pods.get_mut(&pod) {
+            Some((_, newest, _)) if millis < *newest => false,
+            Some((_, newest, seen)) if millis == *newest => {
+                let new = !seen.iter().any(|seen| seen == line);
+                if new {
+                    seen.push(line.to_string());
+                }
+                new
+            }
+            _ => {
+                self.pods
+                    .insert(pod, (time, millis, vec![line.to_string()]));
+                true
+            }
+        }
+    }
+
+    /// Forgets the newest lines of a pod gone away, so resuming does not start at its last line.
+    pub fn forget(&mut self, pod: &str) {
+        let prefix = format!("[pod/{}/", pod);
+        self.pods.retain(|key, _| !key.starts_with(&prefix));
+    }
+
+    /// Arguments for `kubectl logs` to start at the oldest of the newest lines of the pods, or at
+    /// the last line received when the lines carry no timestamps.
+    pub fn since_args(&self, now: Instant) -> Vec<String> {
+        match self.pods.values().min_by_key(|(_, millis, _)| *millis) {
+            Some((time, _, _)) => vec![format!("--since-time={}", time)],
+            None => {
+                let seconds = now.saturating_duration_since(self.received).as_secs() + 1;
+                vec![format!("--since={}s", seconds)]
+            }
+        }
+    }
+}
+
+/// Whether an error of kubectl or the aws cli means the login has to be renewed.
+pub fn auth_expired(error: &str) -> bool {
+    let error = error.to_lowercase();
+    [
+        "unauthorized",
+        "expiredtoken",
+        "token has expired",
+        "sso session",
+        "must be logged in",
+        "provide credentials",
+    ]
+    .iter()
+    .any(|pattern| error.contains(pattern))
+}
+
+#[test]
+fn test_backoff_doubles_up_to_the_maximum() {
+    let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(5));
+    let delays: Vec<u64> = (0..5).map(|_| backoff.next_delay().as_secs()).collect();
+    assert!(delays == [1, 2, 4, 5, 5] && backoff.attempt() == 5);
+    backoff.reset();
+    assert!(backoff.next_delay() == Duration::from_secs(1));
+}
+
+#[test]
+fn test_stream_resume_skips_lines_sent_again() {
+    let start = Instant::now();
+    let mut resume = StreamResume::new(start);
+    assert!(resume.since_args(start + Duration::from_secs(3)) == ["--since=4s"]);
+
+    assert!(resume.keep("[pod/a/c] 2023-11-02T10:04:05.100Z one", start));
+    assert!(resume.keep("[pod/a/c] 2023-11-02T10:04:05.100Z two", start));
+    assert!(resume.keep("[pod/b/c] 2023-11-02T10:04:04.000Z other", start));
+    assert!(resume.keep("  continued", start));
+    assert!(resume.since_args(start) == ["--since-time=2023-11-02T10:04:04.000Z"]);
+
+    // sent again after a reconnect
+    assert!(!resume.keep("[pod/b/c] 2023-11-02T10:04:04.000Z other", start));
+    assert!(!resume.keep("[pod/a/c] 2023-11-02T10:04:05.100Z two", start));
+    assert!(resume.keep("[pod/a/c] 2023-11-02T10:04:05.100Z three", start));
+    assert!(resume.keep("[pod/b/c] 2023-11-02T10:04:06.000Z new", start));
+    assert!(resume.since_args(start) == ["--since-time=2023-11-02T10:04:05.100Z"]);
+
+    resume.keep("[pod/ab/c] 2023-11-02T10:04:01.000Z gone", start);
+    resume.forget("a");
+    assert!(resume.since_args(start) == ["--since-time=2023-11-02T10:04:01.000Z"]);
+    resume.forget("ab");
+    assert!(resume.since_args(start) == ["--since-time=2023-11-02T10:04:06.000Z"]);
+
+    assert!(auth_expired(
+        "error: You must be logged in to the server (Unauthorized)"
+    ));
+    assert!(!auth_expired("error: container is waiting to start"));
+}
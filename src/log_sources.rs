@@ -0,0 +1,95 @@
+use crate::{
+    config::{config, Config},
+    structs::KubeEnv,
+};
+
+/// A log stream followed with `kubectl logs`, shown as a tab of the logs widget.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogSource {
+    pub name: String,
+    /// Label selector of the pods to follow.
+    pub selector: String,
+    pub container: Option<String>,
+    /// Namespace to follow instead of the one picked for the environment.
+    pub namespace: Option<String>,
+    /// Environment to follow instead of the one of the main pane.
+    pub env: Option<KubeEnv>,
+    /// Only lines containing this are kept.
+    pub filter: Option<String>,
+}
+
+/// The log sources of this run, see `LogSource::from_config`.
+pub fn log_sources() -> Vec<LogSource> {
+    LogSource::from_config(config())
+}
+
+impl LogSource {
+    /// One source per `[logs.<name>]` section of the config, sorted by name:
+    ///
+    /// ```ini
+    /// [logs.api]
+    /// selector = component=salespoint-v2
+    /// container = salespoint-v2
+    /// [logs.payments-prod]
+    /// selector = app=payments
+    /// namespace = payments
+    /// env = prod
+    /// filter = ERROR
+    /// ```
+    ///
+    /// Without any, the salespoint logs are followed.
+    pub fn from_config(config: &Config) -> Vec<LogSource> {
+        let mut sources: Vec<LogSource> = config
+            .sections()
+            .filter_map(|(section, values)| {
+                let name = section.strip_prefix("logs.")?;
+                let value = |key: &str| values.get(key).cloned();
+                Some(LogSource {
+                    name: name.to_string(),
+                    selector: value("selector")?,
+                    container: value("container"),
+                    namespace: value("namespace"),
+                    env: match value("env").as_deref() {
+                        Some("dev") => Some(KubeEnv::Dev),
+                        Some("prod") => Some(KubeEnv::Prod),
+                        _ => None,
+                    },
+                    filter: value("filter"),
+                })
+            })
+            .collect();
+        sources.sort_by(|a, b| a.name.cmp(&b.name));
+        if sources.is_empty() {
+            sources.push(LogSource {
+                name: "salespoint".to_string(),
+                selector: "component=salespoint-v2".to_string(),
+                container: Some("salespoint-v2".to_string()),
+                namespace: None,
+                env: None,
+                filter: None,
+            });
+        }
+        sources
+    }
+
+    pub fn keeps(&self, line: &str) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|filter| line.contains(filter.as_str()))
+    }
+}
+
+#[test]
+fn test_log_sources_from_config() {
+    let defaults = LogSource::from_config(&Config::parse(""));
+    assert!(defaults.len() == 1 && defaults[0].selector == "component=salespoint-v2");
+
+    let sources = LogSource::from_config(&Config::parse(
+        "[logs.web]\nselector = app=web\nfilter = ERROR\n[logs.api]\nselector = app=api\nenv = prod\n[logs.broken]\ncontainer = x\n",
+    ));
+    assert!(sources.len() == 2);
+    assert!(sources[0].name == "api" && sources[0].env == Some(KubeEnv::Prod));
+    assert!(sources[1].name == "web" && sources[1].container.is_none());
+    assert!(sources[1].keeps("ERROR boom") && !sources[1].keeps("INFO fine"));
+    assert!(sources[0].keeps("INFO fine"));
+}
@@ -1,29 +1,49 @@
 mod action_handler;
 mod app;
+mod config;
+mod connectivity;
+mod errors;
+mod fuzzy;
+mod keybindings;
+mod kubeconfig;
+mod log_history;
+mod log_merge;
+mod log_reconnect;
+mod log_sources;
+mod namespaces;
+mod pod_watch;
+mod processes;
+mod scheduler;
 mod structs;
+mod theme;
+mod timestamp;
 pub mod truncator;
 mod ui;
 mod widget_data_store;
+mod widget_state;
 mod widgets;
 use app::App;
 use crossterm::{
-    event::{DisableMouseCapture, KeyCode},
+    event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use keybindings::Keybindings;
 use log::{debug, LevelFilter};
 use log4rs::{
     append::file::FileAppender,
     config::{Appender, Root},
     Config,
 };
+use namespaces::RecentNamespaces;
 use ratatui::{layout::Direction, prelude::CrosstermBackend, Terminal};
 use structs::{KubeEnv, Store, TUIAction, TUIEvent};
-use truncator::TopTruncator;
 use widget_data_store::WidgetDataStore;
 use widgets::{
+    create_deployments_widget_data, create_describe_widget_data, create_events_widget_data,
     create_header_widget_data, create_login_widget_data, create_logs_widget_data,
-    create_pods_widget_data, create_tail_widget_data,
+    create_namespaces_widget_data, create_palette_widget_data, create_pods_widget_data,
+    create_split_logs_widget_data, create_split_pods_widget_data, create_tail_widget_data, Keymap,
 };
 
 use std::{
@@ -31,19 +51,13 @@ use std::{
     io,
     sync::{
         mpsc::{self, Receiver, Sender},
-        Once,
+        Arc, Once,
     },
     thread,
 };
 
 fn main() -> Result<(), Box<dyn Error>> {
-    // setup terminal
-    enable_raw_mode()?;
     init_logging()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
 
     // create all needed channels
     let (event_tx, event_rx): (Sender<TUIEvent>, Receiver<TUIEvent>) = mpsc::channel();
@@ -56,6 +70,13 @@ fn main() -> Result<(), Box<dyn Error>> {
     let logs_widget_data = create_logs_widget_data();
     let pods_widget_data = create_pods_widget_data();
     let tail_widget_data = create_tail_widget_data();
+    let events_widget_data = create_events_widget_data();
+    let describe_widget_data = create_describe_widget_data();
+    let deployments_widget_data = create_deployments_widget_data();
+    let split_logs_widget_data = create_split_logs_widget_data();
+    let split_pods_widget_data = create_split_pods_widget_data();
+    let namespaces_widget_data = create_namespaces_widget_data();
+    let palette_widget_data = create_palette_widget_data();
 
     // store
     let mut store = Store::new(
@@ -65,50 +86,92 @@ fn main() -> Result<(), Box<dyn Error>> {
         pods_widget_data.get_widget(),
         tail_widget_data.get_widget(),
     );
-
-    // truncator
-    let truncator = Box::new(TopTruncator::new(50));
+    store.events_widget = Some(events_widget_data.get_widget());
+    store.describe_widget = Some(describe_widget_data.get_widget());
+    store.deployments_widget = Some(deployments_widget_data.get_widget());
+    store.split_logs_widget = Some(split_logs_widget_data.get_widget());
+    store.split_pods_widget = Some(split_pods_widget_data.get_widget());
+    store.namespaces_widget = Some(namespaces_widget_data.get_widget());
+    store.palette_widget = Some(palette_widget_data.get_widget());
+    store.recent_namespaces = RecentNamespaces::load();
+    store.commands = [
+        app::global_commands(),
+        logs_widget_data.get_commands(),
+        pods_widget_data.get_commands(),
+        events_widget_data.get_commands(),
+        deployments_widget_data.get_commands(),
+        split_logs_widget_data.get_commands(),
+        namespaces_widget_data.get_commands(),
+    ]
+    .concat();
+    let command_ids: Vec<&str> = store.commands.iter().map(|command| command.id).collect();
+    store.keybindings = Arc::new(Keybindings::load(&command_ids)?);
 
     // clone to move in to action thread
     let action_tx_clone = action_tx.clone();
 
+    let widget_event_handlers = vec![
+        login_widget_data.get_event_handler(),
+        logs_widget_data.get_event_handler(),
+        describe_widget_data.get_event_handler(),
+        pods_widget_data.get_event_handler(),
+        tail_widget_data.get_event_handler(),
+        events_widget_data.get_event_handler(),
+        deployments_widget_data.get_event_handler(),
+        split_logs_widget_data.get_event_handler(),
+        split_pods_widget_data.get_event_handler(),
+        namespaces_widget_data.get_event_handler(),
+        palette_widget_data.get_event_handler(),
+    ];
+
     // widget data store
     thread::spawn(move || {
-        let mut widget_data_store = WidgetDataStore::new(
-            event_rx,
-            &mut store,
-            store_tx.clone(),
-            action_tx_clone,
-            truncator,
-        );
-
-        let widget_event_handlers = vec![
-            login_widget_data.get_event_handler(),
-            logs_widget_data.get_event_handler(),
-            pods_widget_data.get_event_handler(),
-            tail_widget_data.get_event_handler(),
-        ];
+        let mut widget_data_store =
+            WidgetDataStore::new(event_rx, &mut store, store_tx.clone(), action_tx_clone);
         widget_data_store.start(widget_event_handlers)
     });
 
     // clone to move in to action thread
     let event_tx_clone = event_tx.clone();
+    let action_tx_clone = action_tx.clone();
 
     // action thread
     thread::spawn(move || {
-        action_handler::start(event_tx_clone, action_rx);
+        action_handler::start(event_tx_clone, action_tx_clone, action_rx);
     });
 
     // init state
     event_tx.send(TUIEvent::EnvChange(KubeEnv::Dev)).unwrap();
 
     // package the extended keymaps in a Vec
-    let mut extended_keymap: Vec<fn(KeyCode, &Store, &Sender<TUIEvent>)> = vec![];
+    let mut extended_keymap: Vec<Keymap> = vec![];
     extended_keymap.push(header_widget_data.get_keymap());
+    extended_keymap.push(palette_widget_data.get_keymap());
+    extended_keymap.push(logs_widget_data.get_keymap());
+    extended_keymap.push(namespaces_widget_data.get_keymap());
+    extended_keymap.push(describe_widget_data.get_keymap());
+    extended_keymap.push(deployments_widget_data.get_keymap());
+    extended_keymap.push(pods_widget_data.get_keymap());
+    extended_keymap.push(events_widget_data.get_keymap());
+    extended_keymap.push(split_logs_widget_data.get_keymap());
+
+    // setup terminal
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    // capturing the mouse takes over text selection in most terminals, so it is opt-in
+    if config::config().get_or("ui", "mouse", false) {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
 
     // create app and run it
     let res = App::new(&mut terminal, event_tx, action_tx, &extended_keymap).run_app(store_rx);
 
+    // no kubectl or aws cli is left behind
+    processes::processes().terminate_all(processes::kill_grace());
+
     // restore terminal
     disable_raw_mode()?;
     execute!(
@@ -0,0 +1,113 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use log::warn;
+
+use crate::{
+    config::{config, data_dir},
+    structs::KubeEnv,
+};
+
+const MAX_RECENT: usize = 10;
+
+/// Namespace used for an environment nobody picked a namespace for yet.
+pub fn default_namespace() -> String {
+    config().get_or("kube", "namespace", "myccv-dev-salespoint".to_string())
+}
+
+/// Namespaces picked lately, most recent first, kept per environment in the data dir.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RecentNamespaces {
+    by_env: HashMap<String, Vec<String>>,
+    /// File the namespaces are saved to, `None` keeps them in memory only.
+    path: Option<PathBuf>,
+}
+
+impl RecentNamespaces {
+    pub fn load() -> Self {
+        let path = data_dir().join("recent_namespaces");
+        let mut recent = fs::read_to_string(&path)
+            .map(|text| RecentNamespaces::parse(&text))
+            .unwrap_or_default();
+        recent.path = Some(path);
+        recent
+    }
+
+    pub fn save(&self) {
+        if let Some(path) = &self.path {
+            if let Err(error) =
+                fs::create_dir_all(data_dir()).and_then(|_| fs::write(path, self.to_string()))
+            {
+                warn!("could not save recent namespaces: {}", error);
+            }
+        }
+    }
+
+    /// One `<env> <namespace>` per line.
+    fn parse(text: &str) -> Self {
+        let mut recent = RecentNamespaces::default();
+        for line in text.lines() {
+            if let Some((env, namespace)) = line.trim().split_once(' ') {
+                recent
+                    .by_env
+                    .entry(env.to_string())
+                    .or_default()
+                    .push(namespace.trim().to_string());
+            }
+        }
+        recent
+    }
+
+    pub fn for_env(&self, env: KubeEnv) -> &[String] {
+        self.by_env
+            .get(&format!("{:?}", env))
+            .map(|namespaces| namespaces.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The namespace currently used for `env`.
+    pub fn current(&self, env: KubeEnv) -> String {
+        self.for_env(env)
+            .first()
+            .cloned()
+            .unwrap_or_else(default_namespace)
+    }
+
+    pub fn push(&mut self, env: KubeEnv, namespace: &str) {
+        let namespaces = self.by_env.entry(format!("{:?}", env)).or_default();
+        namespaces.retain(|known| known != namespace);
+        namespaces.insert(0, namespace.to_string());
+        namespaces.truncate(MAX_RECENT);
+    }
+}
+
+impl std::fmt::Display for RecentNamespaces {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut envs: Vec<&String> = self.by_env.keys().collect();
+        envs.sort();
+        for env in envs {
+            for namespace in &self.by_env[env] {
+                writeln!(f, "{} {}", env, namespace)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_recent_namespaces() {
+    let mut recent = RecentNamespaces::parse("Dev one\nDev two\nProd three\nbroken\n");
+    assert!(recent.for_env(KubeEnv::Dev) == ["one", "two"]);
+    assert!(recent.current(KubeEnv::Prod) == "three");
+
+    recent.push(KubeEnv::Dev, "two");
+    recent.push(KubeEnv::Prod, "four");
+    assert!(recent.for_env(KubeEnv::Dev) == ["two", "one"]);
+    assert!(recent.to_string() == "Dev two\nDev one\nProd four\nProd three\n");
+    assert!(RecentNamespaces::parse(&recent.to_string()) == recent);
+
+    for index in 0..20 {
+        recent.push(KubeEnv::Dev, &index.to_string());
+    }
+    assert!(recent.for_env(KubeEnv::Dev).len() == MAX_RECENT);
+    assert!(recent.current(KubeEnv::Dev) == "19");
+}
@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+
+use crate::timestamp::parse_rfc3339;
+
+/// Starts the lines marking where a pod started or terminated in the logs.
+pub const POD_MARKER: &str = "── ";
+
+pub fn pod_marker(pod: &str, what: &str) -> String {
+    format!("{}pod/{} {} ──\n", POD_MARKER, pod, what)
+}
+
+/// A change of the pods whose logs are followed.
+#[derive(Debug, PartialEq)]
+pub enum PodChange {
+    /// The pod runs, `new` when it started after the watch did.
+    Started {
+        pod: String,
+        new: bool,
+    },
+    Terminated(String),
+}
+
+/// The running pods of a selector, from the lines of `kubectl get pods --watch
+/// --output-watch-events` giving the event, name, phase and start time of the changed pod.
+#[derive(Debug)]
+pub struct PodWatch {
+    running: HashSet<String>,
+    /// When the watch started, in seconds since the epoch.
+    started: i64,
+}
+
+impl PodWatch {
+    pub fn new(started: i64) -> Self {
+        PodWatch {
+            running: HashSet::new(),
+            started,
+        }
+    }
+
+    /// The change a line of the watch makes to the running pods, if any.
+    pub fn apply(&mut self, line: &str) -> Option<PodChange> {
+        let mut columns = line.split_whitespace();
+        let (event, pod, phase) = (columns.next()?, columns.next()?, columns.next()?);
+        let running = event != "DELETED" && phase == "Running";
+        match (running, self.running.contains(pod)) {
+            (true, false) => {
+                self.running.insert(pod.to_string());
+                let new = columns
+                    .next()
+                    .and_then(parse_rfc3339)
+                    .is_some_and(|started| started >= self.started);
+                Some(PodChange::Started {
+                    pod: pod.to_string(),
+                    new,
+                })
+            }
+            (false, true) => {
+                self.running.remove(pod);
+                Some(PodChange::Terminated(pod.to_string()))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn test_pod_watch_follows_pods_as_they_come_and_go() {
+    let mut watch = PodWatch::new(1698919445);
+    let started = |pod: &str, new| {
+        Some(PodChange::Started {
+            pod: pod.to_string(),
+            new,
+        })
+    };
+    assert!(watch.apply("ADDED api-1 Running 2023-11-02T09:00:00Z\n") == started("api-1", false));
+    assert!(watch.apply("ADDED api-2 Pending <none>\n").is_none());
+    assert!(watch.apply("MODIFIED api-2 Running 2023-11-02T10:05:00Z\n") == started("api-2", true));
+    assert!(watch
+        .apply("MODIFIED api-2 Running 2023-11-02T10:05:00Z\n")
+        .is_none());
+    assert!(
+        watch.apply("MODIFIED api-1 Succeeded 2023-11-02T09:00:00Z\n")
+            == Some(PodChange::Terminated("api-1".to_string()))
+    );
+    assert!(
+        watch.apply("DELETED api-2 Running 2023-11-02T10:05:00Z\n")
+            == Some(PodChange::Terminated("api-2".to_string()))
+    );
+    assert!(watch
+        .apply("DELETED api-1 Succeeded 2023-11-02T09:00:00Z\n")
+        .is_none());
+    assert!(pod_marker("api-1", "started") == "── pod/api-1 started ──\n");
+}
@@ -0,0 +1,225 @@
+use std::{
+    cell::Cell,
+    io,
+    process::{Child, Command},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use log::debug;
+
+use crate::{config::config, timestamp::human_age};
+
+/// A child process spawned by the app.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Process {
+    pub pid: u32,
+    /// What the process is for, like following the logs of a pod.
+    pub purpose: String,
+    pub started: Instant,
+    /// Action in flight the process was spawned for, see `run_for_action`.
+    pub action: Option<u64>,
+}
+
+/// The child processes running, so none outlives the app.
+#[derive(Debug, Default)]
+pub struct ProcessRegistry {
+    running: Mutex<Vec<Process>>,
+    /// Set once the app quits, when no process is started anymore.
+    closed: AtomicBool,
+}
+
+static PROCESSES: OnceLock<ProcessRegistry> = OnceLock::new();
+
+thread_local! {
+    /// Action in flight the processes spawned on this thread are for.
+    static ACTION: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// Runs `work`, marking the processes it spawns as processes of the action in flight `id`.
+pub fn run_for_action<T>(id: u64, work: impl FnOnce() -> T) -> T {
+    ACTION.with(|action| action.set(Some(id)));
+    let result = work();
+    ACTION.with(|action| action.set(None));
+    result
+}
+
+/// The child processes of this run.
+pub fn processes() -> &'static ProcessRegistry {
+    PROCESSES.get_or_init(ProcessRegistry::default)
+}
+
+/// How long a process is given to exit after SIGTERM before it gets SIGKILL, `kill_grace_ms` in
+/// the `[processes]` section of the config.
+pub fn kill_grace() -> Duration {
+    Duration::from_millis(config().get_or("processes", "kill_grace_ms", 1000))
+}
+
+impl ProcessRegistry {
+    /// Registers a running process unless `terminate_all` started, returning whether it did.
+    pub fn register(&self, pid: u32, purpose: impl Into<String>) -> bool {
+        let mut running = self.running.lock().unwrap();
+        if self.closed.load(Ordering::Relaxed) {
+            return false;
+        }
+        running.push(Process {
+            pid,
+            purpose: purpose.into(),
+            started: Instant::now(),
+            action: ACTION.with(Cell::get),
+        });
+        true
+    }
+
+    /// Forgets a process once it has been waited for.
+    pub fn exited(&self, pid: u32) {
+        self.running
+            .lock()
+            .unwrap()
+            .retain(|process| process.pid != pid);
+    }
+
+    pub fn list(&self) -> Vec<Process> {
+        self.running.lock().unwrap().clone()
+    }
+
+    /// Sends SIGTERM to the processes matching and SIGKILL to the ones not waited for within
+    /// `grace`.
+    pub fn terminate_where(&self, matches: impl Fn(&Process) -> bool, grace: Duration) {
+        let matching = || -> Vec<u32> {
+            self.list()
+                .iter()
+                .filter(|process| matches(process))
+                .map(|process| process.pid)
+                .collect()
+        };
+        let pids = matching();
+        debug!("terminating processes {:?}", pids);
+        for &pid in &pids {
+            signal(pid, libc::SIGTERM);
+        }
+        let deadline = Instant::now() + grace;
+        while !matching().is_empty() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        for pid in matching() {
+            signal(pid, libc::SIGKILL);
+        }
+    }
+
+    /// Terminates every process and refuses the ones spawned afterwards, by threads still running.
+    pub fn terminate_all(&self, grace: Duration) {
+        {
+            let _running = self.running.lock().unwrap();
+            self.closed.store(true, Ordering::Relaxed);
+        }
+        self.terminate_where(|_| true, grace);
+    }
+}
+
+fn signal(pid: u32, signal: libc::c_int) {
+    // SAFETY: kill only sends a signal, to a child of ours that was not waited for yet
+    unsafe {
+        libc::kill(pid as libc::pid_t, signal);
+    }
+}
+
+/// Sends SIGTERM to a child and SIGKILL when it did not exit within `grace`, then waits for it.
+pub fn terminate(child: &mut Child, grace: Duration) -> io::Result<()> {
+    signal(child.id(), libc::SIGTERM);
+    let deadline = Instant::now() + grace;
+    while Instant::now() < deadline {
+        if child.try_wait()?.is_some() {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    child.kill()?;
+    child.wait().map(|_| ())
+}
+
+/// Spawns commands as processes of the registry.
+pub trait SpawnRegistered {
+    /// Spawns the command, registered with its purpose until `exited` is called.
+    fn spawn_as(&mut self, purpose: impl Into<String>) -> io::Result<Child>;
+}
+
+impl SpawnRegistered for Command {
+    fn spawn_as(&mut self, purpose: impl Into<String>) -> io::Result<Child> {
+        let mut child = self.spawn()?;
+        if !processes().register(child.id(), purpose) {
+            child.kill()?;
+            child.wait()?;
+            return Err(io::Error::other("the app is quitting"));
+        }
+        Ok(child)
+    }
+}
+
+/// A line per running process, for the processes panel.
+pub fn processes_text(processes: &[Process]) -> String {
+    if processes.is_empty() {
+        return "No processes running".to_string();
+    }
+    processes
+        .iter()
+        .map(|process| {
+            let action = match process.action {
+                Some(id) => format!(" (action #{})", id),
+                None => String::new(),
+            };
+            format!(
+                "{:>7}  {:>4}  {}{}",
+                process.pid,
+                human_age(process.started.elapsed().as_secs() as i64),
+                process.purpose,
+                action
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[test]
+fn test_processes_are_terminated() {
+    let registry = ProcessRegistry::default();
+    let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+    registry.register(child.id(), "sleeping");
+    assert!(registry.list()[0].purpose == "sleeping");
+    assert!(processes_text(&registry.list()).ends_with("0s  sleeping"));
+
+    let mut other = Command::new("sleep").arg("30").spawn().unwrap();
+    run_for_action(7, || registry.register(other.id(), "sleeping for 7"));
+    assert!(processes_text(&registry.list()).ends_with("sleeping for 7 (action #7)"));
+    registry.terminate_where(
+        |process| process.action == Some(7),
+        Duration::from_millis(50),
+    );
+    assert!(!other.wait().unwrap().success());
+    registry.exited(other.id());
+    assert!(child.try_wait().unwrap().is_none());
+
+    registry.terminate_all(Duration::from_millis(50));
+    assert!(!child.wait().unwrap().success());
+    registry.exited(child.id());
+    assert!(registry.list().is_empty());
+    assert!(!registry.register(child.id(), "spawned while quitting"));
+    assert!(registry.list().is_empty());
+    assert!(processes_text(&[]) == "No processes running");
+
+    // ignores SIGTERM, so it takes the SIGKILL
+    let mut stubborn = Command::new("sh")
+        .arg("-c")
+        .arg("trap '' TERM; exec sleep 30")
+        .spawn()
+        .unwrap();
+    thread::sleep(Duration::from_millis(100));
+    let started = Instant::now();
+    terminate(&mut stubborn, Duration::from_millis(200)).unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(200));
+    assert!(stubborn.try_wait().unwrap().is_some());
+}
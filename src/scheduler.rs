@@ -0,0 +1,149 @@
+use std::time::{Duration, Instant};
+
+use crate::{config::config, structs::TUIAction};
+
+/// Least time between two frames, from `fps` in the `[ui]` section of the config.
+pub fn frame_interval() -> Duration {
+    Duration::from_secs(1) / config().get_or("ui", "fps", 30u32).clamp(1, 240)
+}
+
+/// Keeps track of actions that should be repeated periodically, like refreshing
+/// the pods and deployments tables.
+pub struct RefreshScheduler {
+    jobs: Vec<RefreshJob>,
+}
+
+struct RefreshJob {
+    action: TUIAction,
+    interval: Duration,
+    next_run: Instant,
+}
+
+impl RefreshScheduler {
+    pub fn new() -> Self {
+        RefreshScheduler { jobs: vec![] }
+    }
+
+    /// Schedules `action` every `interval`, replacing an earlier schedule of the same action.
+    pub fn schedule(&mut self, action: TUIAction, interval: Duration) {
+        self.jobs.retain(|job| job.action != action);
+        self.jobs.push(RefreshJob {
+            action,
+            interval,
+            next_run: Instant::now() + interval,
+        });
+    }
+
+    /// Stops repeating `action`.
+    pub fn cancel(&mut self, action: &TUIAction) {
+        self.jobs.retain(|job| job.action != *action);
+    }
+
+    /// Time until the next job is due, or a long nap when nothing is scheduled.
+    pub fn time_until_next(&self) -> Duration {
+        self.jobs
+            .iter()
+            .map(|job| job.next_run.saturating_duration_since(Instant::now()))
+            .min()
+            .unwrap_or(Duration::from_secs(60))
+    }
+
+    /// Returns the actions that are due and moves their next run one interval ahead.
+    pub fn due(&mut self) -> Vec<TUIAction> {
+        let now = Instant::now();
+        self.jobs
+            .iter_mut()
+            .filter(|job| job.next_run <= now)
+            .map(|job| {
+                job.next_run = now + job.interval;
+                job.action.clone()
+            })
+            .collect()
+    }
+}
+
+/// Decides when the UI draws: only when something changed, at most once per frame. Nothing
+/// changing means nothing to wake up for, the UI then sleeps until the next update.
+pub struct RenderScheduler {
+    frame: Duration,
+    last_draw: Option<Instant>,
+    changed: bool,
+}
+
+impl RenderScheduler {
+    pub fn new(frame: Duration) -> Self {
+        RenderScheduler {
+            frame,
+            last_draw: None,
+            changed: true,
+        }
+    }
+
+    /// The store changed, a key was pressed or the terminal resized.
+    pub fn changed(&mut self) {
+        self.changed = true;
+    }
+
+    /// Whether to draw now, remembering the frame as drawn when it is.
+    pub fn draw_now(&mut self, now: Instant) -> bool {
+        let frame_over = self
+            .last_draw
+            .is_none_or(|last_draw| now >= last_draw + self.frame);
+        if self.changed && frame_over {
+            self.last_draw = Some(now);
+            self.changed = false;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// How long to wait for updates before the pending frame is due, `None` when idle.
+    pub fn wait(&self, now: Instant) -> Option<Duration> {
+        match (self.changed, self.last_draw) {
+            (false, _) => None,
+            (true, None) => Some(Duration::ZERO),
+            (true, Some(last_draw)) => {
+                Some((last_draw + self.frame).saturating_duration_since(now))
+            }
+        }
+    }
+}
+
+#[test]
+fn test_refresh_scheduler() {
+    let mut scheduler = RefreshScheduler::new();
+    assert!(scheduler.due().is_empty());
+    assert!(scheduler.time_until_next() == Duration::from_secs(60));
+
+    scheduler.schedule(TUIAction::GetPods, Duration::from_millis(10));
+    scheduler.schedule(TUIAction::GetDeployments, Duration::from_secs(10));
+    scheduler.schedule(TUIAction::GetPods, Duration::from_millis(20));
+    assert!(scheduler.time_until_next() <= Duration::from_millis(20));
+    assert!(scheduler.due().is_empty());
+
+    std::thread::sleep(Duration::from_millis(30));
+    assert!(scheduler.due() == vec![TUIAction::GetPods]);
+    assert!(scheduler.due().is_empty());
+
+    scheduler.cancel(&TUIAction::GetDeployments);
+    scheduler.cancel(&TUIAction::GetPods);
+    assert!(scheduler.time_until_next() == Duration::from_secs(60));
+}
+
+#[test]
+fn test_render_scheduler_draws_changes_once_per_frame() {
+    let start = Instant::now();
+    let frame = Duration::from_millis(50);
+    let mut scheduler = RenderScheduler::new(frame);
+    assert!(scheduler.draw_now(start));
+    assert!(scheduler.wait(start).is_none());
+    assert!(!scheduler.draw_now(start + frame));
+
+    scheduler.changed();
+    scheduler.changed();
+    assert!(scheduler.wait(start + frame / 5) == Some(frame * 4 / 5));
+    assert!(!scheduler.draw_now(start + frame / 5));
+    assert!(scheduler.draw_now(start + frame));
+    assert!(!scheduler.draw_now(start + frame * 3));
+}
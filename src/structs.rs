@@ -1,13 +1,24 @@
-use std::{collections::HashMap, sync::mpsc::Sender};
+use std::{
+    sync::{mpsc::Sender, Arc},
+    time::Duration,
+};
 
-use crate::widgets::{BodyWidget, CliWidgetId, HeaderWidget};
+use crate::{
+    errors::ErrorReport,
+    keybindings::Keybindings,
+    namespaces::RecentNamespaces,
+    widget_state::{LogExport, WidgetState},
+    widgets::{BodyWidget, CliWidgetId, Command, HeaderWidget},
+};
 
 pub const DEV: KubeEnvData =
     KubeEnvData::new("eks-non-prod-myccv-lab-developer", "shared-non-prod-2");
 pub const PROD: KubeEnvData = KubeEnvData::new("eks-prod-myccv-lab-developer", "shared-prod-2");
 
+#[derive(Clone, Copy)]
 pub struct KubeEnvData<'a> {
     pub profile: &'a str,
+    /// Name of the EKS cluster, also used as the name of its kubeconfig context.
     pub environment: &'a str,
 }
 
@@ -27,11 +38,35 @@ pub struct Store {
     pub logged_in: bool,
     pub env_change_possible: bool,
     pub login_code: Option<String>,
+    pub pending_rollout_undo: Option<String>,
+    pub kube_env: Option<KubeEnv>,
+    /// Environment shown in the right hand pane, when the view is split.
+    pub split_env: Option<KubeEnv>,
+    pub namespace_picker_open: bool,
+    pub recent_namespaces: RecentNamespaces,
+    pub palette_open: bool,
+    /// Everything the command palette offers, registered by the widgets.
+    pub commands: Vec<Command>,
+    pub keybindings: Arc<Keybindings>,
+    pub quit: bool,
+    /// Shows the keys of the current context on top of everything.
+    pub help_open: bool,
+    /// Shows the child processes running on top of everything.
+    pub processes_open: bool,
+    /// Shows the history of the errors on top of everything.
+    pub errors_open: bool,
     pub header_widget: Option<HeaderWidget>,
     pub login_widget: Option<BodyWidget>,
     pub logs_widget: Option<BodyWidget>,
     pub pods_widget: Option<BodyWidget>,
     pub tail_widget: Option<BodyWidget>,
+    pub events_widget: Option<BodyWidget>,
+    pub describe_widget: Option<BodyWidget>,
+    pub deployments_widget: Option<BodyWidget>,
+    pub split_logs_widget: Option<BodyWidget>,
+    pub split_pods_widget: Option<BodyWidget>,
+    pub namespaces_widget: Option<BodyWidget>,
+    pub palette_widget: Option<BodyWidget>,
 }
 
 impl Store {
@@ -48,46 +83,118 @@ impl Store {
             logged_in: false,
             env_change_possible: false,
             login_code: None,
+            pending_rollout_undo: None,
+            kube_env: None,
+            split_env: None,
+            namespace_picker_open: false,
+            recent_namespaces: RecentNamespaces::default(),
+            palette_open: false,
+            commands: vec![],
+            keybindings: Arc::default(),
+            quit: false,
+            help_open: false,
+            processes_open: false,
+            errors_open: false,
             header_widget: Some(header_widget),
             login_widget: Some(login_widget),
             logs_widget: Some(logs_widget),
             pods_widget: Some(pods_widget),
             tail_widget: Some(tail_widget),
+            events_widget: None,
+            describe_widget: None,
+            deployments_widget: None,
+            split_logs_widget: None,
+            split_pods_widget: None,
+            namespaces_widget: None,
+            palette_widget: None,
+        }
+    }
+
+    /// The widgets of the main view, the split panes replacing deployments and events.
+    pub fn visible_widgets(&self) -> Vec<&BodyWidget> {
+        let widgets = if self.split_env.is_some() {
+            [
+                &self.pods_widget,
+                &self.logs_widget,
+                &self.split_pods_widget,
+                &self.split_logs_widget,
+            ]
+        } else {
+            [
+                &self.logs_widget,
+                &self.pods_widget,
+                &self.deployments_widget,
+                &self.events_widget,
+            ]
+        };
+        widgets.into_iter().flatten().collect()
+    }
+
+    pub fn body_widget_mut(&mut self, id: &CliWidgetId) -> Option<&mut BodyWidget> {
+        match id {
+            CliWidgetId::GetLogs => self.logs_widget.as_mut(),
+            CliWidgetId::GetPods => self.pods_widget.as_mut(),
+            CliWidgetId::GetEvents => self.events_widget.as_mut(),
+            CliWidgetId::GetDeployments => self.deployments_widget.as_mut(),
+            CliWidgetId::SplitLogs => self.split_logs_widget.as_mut(),
+            CliWidgetId::SplitPods => self.split_pods_widget.as_mut(),
+            CliWidgetId::DescribePod => self.describe_widget.as_mut(),
+            _ => None,
         }
     }
 }
 
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct CliWidgetData {
-    pub id: CliWidgetId,
-    pub thread_started: bool,
     pub initiate_thread: Option<fn(action_tx: &Sender<TUIAction>)>,
-    pub data: HashMap<String, Option<Vec<String>>>,
+    pub state: Box<dyn WidgetState>,
 }
 
 impl CliWidgetData {
-    pub fn new(id: CliWidgetId) -> Self {
+    pub fn new(state: impl WidgetState + 'static) -> Self {
         CliWidgetData {
-            id,
-            thread_started: false,
             initiate_thread: None,
-            data: HashMap::default(),
+            state: Box::new(state),
         }
     }
+
+    /// Like `new`, for a widget starting its refreshes on the action thread once logged in.
+    pub fn with_thread(
+        initiate_thread: fn(action_tx: &Sender<TUIAction>),
+        state: impl WidgetState + 'static,
+    ) -> Self {
+        CliWidgetData {
+            initiate_thread: Some(initiate_thread),
+            state: Box::new(state),
+        }
+    }
+
+    /// The state of the widget as the type its factory created it with.
+    pub fn state<S: WidgetState + 'static>(&self) -> &S {
+        self.state
+            .as_any()
+            .downcast_ref()
+            .expect("widget state of another type")
+    }
+
+    pub fn state_mut<S: WidgetState + 'static>(&mut self) -> &mut S {
+        self.state
+            .as_any_mut()
+            .downcast_mut()
+            .expect("widget state of another type")
+    }
 }
 
 #[derive(Clone, Default, Debug, PartialEq)]
 pub enum UIState {
     #[default]
     Init,
-    UserInput,
     LoggingIn,
-    LoggedIn,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum TUIEvent {
-    Error(TUIError),
+    Error(ErrorReport),
     CheckConnectivity,
     ClearError,
     RequestLoginStart,
@@ -99,19 +206,86 @@ pub enum TUIEvent {
     IsLoggedIn,
     IsConnected,
     AddLoginLog(String),
-    AddLog(String),
+    /// Lines of the log source at an index of `log_sources`.
+    AddLog(usize, String),
+    /// What goes wrong following a log source, `None` once it is followed again.
+    LogStreamStatus(usize, Option<String>),
     AddPods(String),
     AddTailLog(String),
+    AddEvents(String),
+    Navigate(Direction2),
+    ToggleEventsFilter,
+    DescribePod,
+    AddPodDescription(String),
+    ClosePodDescription,
+    NextDescribeSection,
+    ToggleDescribeSection,
+    AddDeployments(String),
+    RequestRolloutUndo,
+    ConfirmRolloutUndo,
+    CancelRolloutUndo,
+    AddTop(String),
+    AddPodLimits(String),
+    ToggleSplit,
+    AddSplitLog(KubeEnv, String),
+    AddSplitPods(KubeEnv, String),
+    OpenNamespacePicker,
+    AddNamespaces(String),
+    SetNamespaceFilter(String),
+    MoveNamespaceSelection(Direction2),
+    SelectNamespace,
+    CloseNamespacePicker,
+    OpenPalette,
+    SetPaletteFilter(String),
+    MovePaletteSelection(Direction2),
+    ClosePalette,
+    /// Hands an action to the action thread as is.
+    RunAction(TUIAction),
+    Quit,
+    ToggleHelp,
+    ToggleProcesses,
+    ToggleErrors,
+    /// Scrolls the error history by a number of lines, up when negative, given the offset that
+    /// shows its end.
+    ScrollErrors(i32, u16),
+    /// An action runs on a thread of its own, by id and with how it is shown.
+    ActionStarted(u64, String),
+    ActionFinished(u64),
+    SelectNextAction,
+    CancelSelectedAction,
+    /// Short tip shown in the header until the next bound key.
+    ShowHint(String),
+    ClearHint,
+    FocusWidget(CliWidgetId),
+    /// Scrolls a log view or the pod description by a number of lines, given the offset that
+    /// shows its end.
+    ScrollWidget(CliWidgetId, i32, u16),
+    /// Selects the row shown on a line of a table widget.
+    SelectRow(CliWidgetId, usize),
+    /// Shows the logs tab a number of tabs away.
+    MoveLogTab(isize),
+    OpenLogSearch,
+    SetLogSearch(String),
+    /// Shows the match of the log search before the last one.
+    FindInLogs,
+    CloseLogSearch,
+    /// Writes the logs in memory and on disk to a file.
+    ExportLogs,
+    /// Shows the timestamps of the log lines the next way of `TimeColumn`.
+    CycleLogTime,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TUIError {
     VPN,
-    KEY(String),
+    /// The API server could not be reached, with what went wrong.
+    Unreachable(String),
     API(String),
+    /// Writing an export failed, with why.
+    Export(String),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TUIAction {
     CheckConnectivity,
     LogIn,
@@ -119,13 +293,25 @@ pub enum TUIAction {
     GetLogs,
     GetPods,
     GetTail,
-}
-
-#[derive(Debug, PartialEq)]
-pub enum UserInput {
-    Quit,
-    ChangeEnv,
-    Direction(Direction2),
+    GetEvents,
+    DescribePod(String),
+    GetDeployments,
+    RolloutUndo(String),
+    GetTop,
+    /// Runs the action now and then again every interval.
+    Refresh(Box<TUIAction>, Duration),
+    /// Stops repeating an action scheduled with `Refresh`.
+    CancelRefresh(Box<TUIAction>),
+    /// Cancels an action in flight by id.
+    CancelAction(u64),
+    OpenSplit(KubeEnv),
+    CloseSplit,
+    GetSplitPods(KubeEnv),
+    GetNamespaces,
+    /// Points the commands for an environment at another namespace.
+    SetNamespace(KubeEnv, String),
+    /// Writes the logs as they were when asked for to a file in the data dir.
+    ExportLogs(LogExport),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -136,8 +322,25 @@ pub enum Direction2 {
     Down,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum KubeEnv {
     Dev,
     Prod,
 }
+
+impl KubeEnv {
+    pub fn data(&self) -> KubeEnvData<'static> {
+        match self {
+            KubeEnv::Dev => DEV,
+            KubeEnv::Prod => PROD,
+        }
+    }
+
+    /// The environment to compare with in the split view.
+    pub fn other(&self) -> KubeEnv {
+        match self {
+            KubeEnv::Dev => KubeEnv::Prod,
+            KubeEnv::Prod => KubeEnv::Dev,
+        }
+    }
+}
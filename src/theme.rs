@@ -0,0 +1,211 @@
+use std::{env, sync::OnceLock};
+
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::config::{config, Config};
+
+/// Colours used by the widgets, picked from the `[theme]` section of the config:
+///
+/// ```ini
+/// [theme]
+/// name = high-contrast
+/// focus = #ff8700
+/// ```
+///
+/// `name` selects one of the built-in themes (`dark`, `light`, `high-contrast`, `no-colour`),
+/// the other keys override a single colour of it. Setting `NO_COLOR` always selects `no-colour`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    pub background: Color,
+    pub foreground: Color,
+    pub border: Color,
+    /// Border of the focused widget.
+    pub focus: Color,
+    /// Added to the focused border, the only way to spot it without colours.
+    pub focus_modifier: Modifier,
+    /// Labels and headers that stand out without meaning anything is wrong.
+    pub accent: Color,
+    pub ok: Color,
+    pub warning: Color,
+    pub error: Color,
+    pub dev: Color,
+    pub prod: Color,
+    pub log_error: Color,
+    pub log_warning: Color,
+    pub log_debug: Color,
+    /// Prefixes of the log sources in the tab showing all of them, used in turn.
+    pub sources: [Color; 4],
+}
+
+const DARK: Theme = Theme {
+    background: Color::Black,
+    foreground: Color::White,
+    border: Color::White,
+    focus: Color::Red,
+    focus_modifier: Modifier::empty(),
+    accent: Color::Cyan,
+    ok: Color::LightGreen,
+    warning: Color::Yellow,
+    error: Color::Red,
+    dev: Color::Green,
+    prod: Color::Red,
+    log_error: Color::Red,
+    log_warning: Color::Yellow,
+    log_debug: Color::DarkGray,
+    sources: [
+        Color::Cyan,
+        Color::Magenta,
+        Color::LightBlue,
+        Color::LightGreen,
+    ],
+};
+
+const LIGHT: Theme = Theme {
+    background: Color::White,
+    foreground: Color::Black,
+    border: Color::Black,
+    focus: Color::Red,
+    focus_modifier: Modifier::empty(),
+    accent: Color::Blue,
+    ok: Color::Green,
+    warning: Color::Magenta,
+    error: Color::Red,
+    dev: Color::Green,
+    prod: Color::Red,
+    log_error: Color::Red,
+    log_warning: Color::Magenta,
+    log_debug: Color::DarkGray,
+    sources: [Color::Blue, Color::Magenta, Color::Cyan, Color::Green],
+};
+
+const HIGH_CONTRAST: Theme = Theme {
+    background: Color::Black,
+    foreground: Color::White,
+    border: Color::Gray,
+    focus: Color::LightYellow,
+    focus_modifier: Modifier::BOLD,
+    accent: Color::LightCyan,
+    ok: Color::LightGreen,
+    warning: Color::LightYellow,
+    error: Color::LightRed,
+    dev: Color::LightGreen,
+    prod: Color::LightRed,
+    log_error: Color::LightRed,
+    log_warning: Color::LightYellow,
+    log_debug: Color::Gray,
+    sources: [
+        Color::LightCyan,
+        Color::LightMagenta,
+        Color::LightBlue,
+        Color::LightGreen,
+    ],
+};
+
+const NO_COLOUR: Theme = Theme {
+    background: Color::Reset,
+    foreground: Color::Reset,
+    border: Color::Reset,
+    focus: Color::Reset,
+    focus_modifier: Modifier::REVERSED,
+    accent: Color::Reset,
+    ok: Color::Reset,
+    warning: Color::Reset,
+    error: Color::Reset,
+    dev: Color::Reset,
+    prod: Color::Reset,
+    log_error: Color::Reset,
+    log_warning: Color::Reset,
+    log_debug: Color::Reset,
+    sources: [Color::Reset; 4],
+};
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// The theme of this run, loaded on first use.
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(|| {
+        let no_color = env::var("NO_COLOR").is_ok_and(|value| !value.is_empty());
+        Theme::from_config(config(), no_color)
+    })
+}
+
+impl Theme {
+    pub fn from_config(config: &Config, no_color: bool) -> Self {
+        if no_color {
+            return NO_COLOUR;
+        }
+        let base = match config.get("theme", "name").unwrap_or("dark") {
+            "light" => LIGHT,
+            "high-contrast" => HIGH_CONTRAST,
+            "no-colour" | "no-color" => NO_COLOUR,
+            _ => DARK,
+        };
+        let colour = |key: &str, default: Color| config.get_or("theme", key, default);
+        Theme {
+            background: colour("background", base.background),
+            foreground: colour("foreground", base.foreground),
+            border: colour("border", base.border),
+            focus: colour("focus", base.focus),
+            focus_modifier: base.focus_modifier,
+            accent: colour("accent", base.accent),
+            ok: colour("ok", base.ok),
+            warning: colour("warning", base.warning),
+            error: colour("error", base.error),
+            dev: colour("dev", base.dev),
+            prod: colour("prod", base.prod),
+            log_error: colour("log_error", base.log_error),
+            log_warning: colour("log_warning", base.log_warning),
+            log_debug: colour("log_debug", base.log_debug),
+            sources: base.sources,
+        }
+    }
+
+    pub fn text(&self) -> Style {
+        Style::new().fg(self.foreground).bg(self.background)
+    }
+
+    pub fn border(&self, focused: bool) -> Style {
+        if focused {
+            Style::new()
+                .fg(self.focus)
+                .add_modifier(self.focus_modifier)
+        } else {
+            Style::new().fg(self.border)
+        }
+    }
+
+    /// Colours the prefix of the log source at `index`, bold so it stands out without colours.
+    pub fn source(&self, index: usize) -> Style {
+        Style::new()
+            .fg(self.sources[index % self.sources.len()])
+            .add_modifier(Modifier::BOLD)
+    }
+
+    /// Colours a log line by the level it mentions.
+    pub fn log_line(&self, line: &str) -> Style {
+        if line.contains("ERROR") {
+            Style::new().fg(self.log_error)
+        } else if line.contains("WARN") {
+            Style::new().fg(self.log_warning)
+        } else if line.contains("DEBUG") || line.contains("TRACE") {
+            Style::new().fg(self.log_debug)
+        } else {
+            Style::new()
+        }
+    }
+}
+
+#[test]
+fn test_theme_from_config() {
+    assert!(Theme::from_config(&Config::parse(""), false) == DARK);
+    assert!(Theme::from_config(&Config::parse("[theme]\nname = light\n"), true) == NO_COLOUR);
+
+    let theme = Theme::from_config(
+        &Config::parse("[theme]\nname = high-contrast\nfocus = #ff8700\nerror = nope\n"),
+        false,
+    );
+    assert!(theme.focus == Color::Rgb(0xff, 0x87, 0x00));
+    assert!(theme.error == HIGH_CONTRAST.error);
+    assert!(theme.border(true).add_modifier.contains(Modifier::BOLD));
+    assert!(theme.log_line("2024 WARN slow response").fg == Some(theme.log_warning));
+}
@@ -0,0 +1,88 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::DateTime;
+
+/// Parses an RFC3339 timestamp as printed by kubectl (`2023-11-02T10:04:05Z`,
+/// optionally with fractional seconds or a `+01:00` offset) into seconds since
+/// the unix epoch.
+pub fn parse_rfc3339(text: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(text.trim())
+        .ok()
+        .map(|time| time.timestamp())
+}
+
+/// Like `parse_rfc3339`, in milliseconds.
+pub fn parse_rfc3339_millis(text: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(text.trim())
+        .ok()
+        .map(|time| time.timestamp_millis())
+}
+
+/// Milliseconds since the unix epoch for the current system time.
+pub fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Seconds since the unix epoch for the current system time.
+pub fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Formats a number of seconds the way kubectl prints ages: `45s`, `3m`, `5h`, `2d`.
+pub fn human_age(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    if seconds < 120 {
+        format!("{}s", seconds)
+    } else if seconds < 120 * 60 {
+        format!("{}m", seconds / 60)
+    } else if seconds < 48 * 3600 {
+        format!("{}h", seconds / 3600)
+    } else {
+        format!("{}d", seconds / 86400)
+    }
+}
+
+#[test]
+fn test_parse_rfc3339() {
+    assert_eq!(parse_rfc3339("1970-01-01T00:00:00Z"), Some(0));
+    assert_eq!(parse_rfc3339("2023-11-02T10:04:05Z"), Some(1698919445));
+    assert_eq!(
+        parse_rfc3339("2023-11-02T10:04:05.123456789Z"),
+        Some(1698919445)
+    );
+    assert_eq!(parse_rfc3339("2023-11-02T11:04:05+01:00"), Some(1698919445));
+    assert_eq!(parse_rfc3339("<none>"), None);
+    assert_eq!(parse_rfc3339("2023-13-02T10:04:05Z"), None);
+    assert_eq!(parse_rfc3339("2023-02-31T10:04:05Z"), None);
+    assert_eq!(parse_rfc3339("2023-11-02T-1:04:05Z"), None);
+    assert_eq!(parse_rfc3339("2023-11-02T10:04:61Z"), None);
+    assert_eq!(parse_rfc3339("2023-11-02T10:04:05+0ä00"), None);
+    assert_eq!(parse_rfc3339("2023-11-02T09:34:05-00:30"), Some(1698919445));
+    assert_eq!(
+        parse_rfc3339_millis("2023-11-02T10:04:05.123456789Z"),
+        Some(1698919445123)
+    );
+    assert_eq!(
+        parse_rfc3339_millis("2023-11-02T11:04:05.5+01:00"),
+        Some(1698919445500)
+    );
+    assert_eq!(
+        parse_rfc3339_millis("2023-11-02T10:04:05Z"),
+        Some(1698919445000)
+    );
+}
+
+#[test]
+fn test_human_age() {
+    assert_eq!(human_age(-3), "0s");
+    assert_eq!(human_age(59), "59s");
+    assert_eq!(human_age(180), "3m");
+    assert_eq!(human_age(5 * 3600), "5h");
+    assert_eq!(human_age(3 * 86400), "3d");
+}
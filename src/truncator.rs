@@ -1,79 +1,201 @@
-use std::time::SystemTime;
-
-use crate::{structs::Store, widgets::RenderWidget};
-
-pub trait Truncatorix {
-    fn set_time(&mut self, now: SystemTime);
-    fn get_time(&self) -> SystemTime;
-    fn truncate(&mut self, store: &mut Store);
-    fn poll(&mut self) -> Option<()> {
-        if self.get_time().elapsed().unwrap().as_secs() > 5 {
-            self.set_time(SystemTime::now());
-            Some(())
-        } else {
-            None
-        }
-    }
-    fn start(&mut self) {
-        self.set_time(SystemTime::now())
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use crate::{config::config, widget_state::LOG_CAPACITY};
+
+/// Where a line of a log stands, counted from the newest line with the line itself included.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LinePosition {
+    pub lines: usize,
+    pub bytes: usize,
+    /// Time since the line was received.
+    pub age: Duration,
+}
+
+/// Decides which of the oldest lines of a log to drop, every time lines are added to it.
+///
+/// Lines older than a kept one are expected to be kept as well, unless `outlives` says the kept
+/// one may be older, so a log only looks at its oldest lines.
+pub trait Truncatorix: Debug + Send + Sync {
+    fn drops(&self, line: &str, position: LinePosition) -> bool;
+
+    /// Whether `line` may be kept while newer lines are dropped.
+    fn outlives(&self, _line: &str) -> bool {
+        false
     }
 }
 
+/// Keeps the newest `max_lines` lines.
+#[derive(Debug)]
 pub struct TopTruncator {
-    now: Option<SystemTime>,
-    from_to_top: i16,
+    max_lines: usize,
 }
 
 impl TopTruncator {
-    pub fn new(from_to_top: i16) -> Self {
-        TopTruncator {
-            now: None,
-            from_to_top,
-        }
+    pub fn new(max_lines: usize) -> Self {
+        TopTruncator { max_lines }
     }
 }
 
 impl Truncatorix for TopTruncator {
-    fn set_time(&mut self, now: SystemTime) {
-        self.now = Some(now)
+    fn drops(&self, _: &str, position: LinePosition) -> bool {
+        position.lines > self.max_lines
     }
+}
 
-    fn get_time(&self) -> SystemTime {
-        self.now.unwrap()
+/// Keeps the newest lines up to `max_bytes` of text.
+#[derive(Debug)]
+pub struct BytesTruncator {
+    max_bytes: usize,
+}
+
+impl BytesTruncator {
+    pub fn new(max_bytes: usize) -> Self {
+        BytesTruncator { max_bytes }
     }
+}
 
-    fn truncate(&mut self, store: &mut Store) {
-        if let Some(widget) = &mut store.logs_widget {
-            if let Some(Some(data)) = widget.get_data().data.get_mut("logs") {
-                let truncate_index = data.len() as i16 - self.from_to_top;
-                if truncate_index > 0 {
-                    widget.set_data("logs".to_string(), data.split_off(truncate_index as usize));
-                }
-            }
-        }
+impl Truncatorix for BytesTruncator {
+    fn drops(&self, _: &str, position: LinePosition) -> bool {
+        position.bytes > self.max_bytes
+    }
+}
+
+/// Keeps the lines received within `max_age`.
+#[derive(Debug)]
+pub struct AgeTruncator {
+    max_age: Duration,
+}
+
+impl AgeTruncator {
+    pub fn new(max_age: Duration) -> Self {
+        AgeTruncator { max_age }
+    }
+}
+
+impl Truncatorix for AgeTruncator {
+    fn drops(&self, _: &str, position: LinePosition) -> bool {
+        position.age > self.max_age
     }
 }
 
-pub struct NoopTruncator {
-    _time_elapsed: i32,
-    _store: Option<Store>,
+/// Keeps lines mentioning `ERROR` `factor` times longer than `truncator` would.
+#[derive(Debug)]
+pub struct ErrorKeepingTruncator {
+    truncator: Box<dyn Truncatorix>,
+    factor: u32,
 }
 
-impl NoopTruncator {
-    pub fn new() -> Self {
-        NoopTruncator {
-            _time_elapsed: 0,
-            _store: None,
+impl ErrorKeepingTruncator {
+    pub fn new(truncator: Box<dyn Truncatorix>, factor: u32) -> Self {
+        ErrorKeepingTruncator {
+            truncator,
+            factor: factor.max(1),
         }
     }
 }
 
-impl Truncatorix for NoopTruncator {
-    fn set_time(&mut self, _: SystemTime) {}
+impl Truncatorix for ErrorKeepingTruncator {
+    fn outlives(&self, line: &str) -> bool {
+        line.contains("ERROR") || self.truncator.outlives(line)
+    }
 
-    fn get_time(&self) -> SystemTime {
-        SystemTime::now()
+    fn drops(&self, line: &str, position: LinePosition) -> bool {
+        if line.contains("ERROR") {
+            let factor = self.factor as usize;
+            self.truncator.drops(
+                line,
+                LinePosition {
+                    lines: position.lines.div_ceil(factor),
+                    bytes: position.bytes.div_ceil(factor),
+                    age: position.age / self.factor,
+                },
+            )
+        } else {
+            self.truncator.drops(line, position)
+        }
     }
+}
+
+/// Drops a line as soon as one of its truncators does.
+#[derive(Debug)]
+pub struct AnyTruncator {
+    truncators: Vec<Box<dyn Truncatorix>>,
+}
+
+impl AnyTruncator {
+    pub fn new(truncators: Vec<Box<dyn Truncatorix>>) -> Self {
+        AnyTruncator { truncators }
+    }
+}
+
+impl Truncatorix for AnyTruncator {
+    fn outlives(&self, line: &str) -> bool {
+        self.truncators
+            .iter()
+            .any(|truncator| truncator.outlives(line))
+    }
+
+    fn drops(&self, line: &str, position: LinePosition) -> bool {
+        self.truncators
+            .iter()
+            .any(|truncator| truncator.drops(line, position))
+    }
+}
+
+/// The truncation policy of a log widget, from its `[truncate.<widget>]` config section:
+///
+/// ```ini
+/// [truncate.logs]
+/// max_lines = 10000
+/// # 0 leaves the size and the age unlimited
+/// max_bytes = 5000000
+/// max_age_secs = 3600
+/// # lines mentioning ERROR are kept 4 times longer
+/// keep_errors = 4
+/// ```
+pub fn widget_truncator(widget: &str) -> Arc<dyn Truncatorix> {
+    let section = format!("truncate.{}", widget);
+    let max_lines = config().get_or(&section, "max_lines", LOG_CAPACITY);
+    let max_bytes = config().get_or(&section, "max_bytes", 0usize);
+    let max_age_secs = config().get_or(&section, "max_age_secs", 0u64);
+    let keep_errors = config().get_or(&section, "keep_errors", 1u32);
+
+    let mut truncators: Vec<Box<dyn Truncatorix>> = vec![Box::new(TopTruncator::new(max_lines))];
+    if max_bytes > 0 {
+        truncators.push(Box::new(BytesTruncator::new(max_bytes)));
+    }
+    if max_age_secs > 0 {
+        truncators.push(Box::new(AgeTruncator::new(Duration::from_secs(
+            max_age_secs,
+        ))));
+    }
+    let truncator = AnyTruncator::new(truncators);
+    if keep_errors > 1 {
+        Arc::new(ErrorKeepingTruncator::new(Box::new(truncator), keep_errors))
+    } else {
+        Arc::new(truncator)
+    }
+}
 
-    fn truncate(&mut self, _: &mut Store) {}
+#[test]
+fn test_truncators_compose() {
+    let truncator = ErrorKeepingTruncator::new(
+        Box::new(AnyTruncator::new(vec![
+            Box::new(TopTruncator::new(10)),
+            Box::new(BytesTruncator::new(100)),
+            Box::new(AgeTruncator::new(Duration::from_secs(60))),
+        ])),
+        2,
+    );
+    let position = |lines, bytes, age| LinePosition {
+        lines,
+        bytes,
+        age: Duration::from_secs(age),
+    };
+    assert!(!truncator.drops("info", position(10, 100, 60)));
+    assert!(truncator.drops("info", position(11, 0, 0)));
+    assert!(truncator.drops("info", position(1, 101, 0)));
+    assert!(truncator.drops("info", position(1, 0, 61)));
+    assert!(!truncator.drops("ERROR boom", position(20, 200, 120)));
+    assert!(truncator.drops("ERROR boom", position(22, 0, 0)));
 }
@@ -3,6 +3,7 @@ use std::rc::Rc;
 
 use ratatui::{
     prelude::{Constraint, Direction, Layout, Rect},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
     Frame,
 };
 
@@ -40,14 +41,24 @@ impl SingleLayoutUI {
 #[derive(Clone)]
 pub struct MainLayoutUI<'a> {
     pub draw_frame: Option<fn() -> &'a mut Frame<'a>>,
+    /// Shows two environments side by side, each with its own pods and logs.
+    pub split: bool,
 }
 
 impl<'a> MainLayoutUI<'a> {
     pub fn new() -> Self {
-        MainLayoutUI { draw_frame: None }
+        MainLayoutUI {
+            draw_frame: None,
+            split: false,
+        }
     }
 
     pub fn get_full_rect(&self, f: &mut Frame<'_>) -> Rc<[Rect]> {
+        self.full_rects(f.size())
+    }
+
+    /// The rect of a full screen widget, for a terminal of size `area`.
+    pub fn full_rects(&self, area: Rect) -> Rc<[Rect]> {
         let main_layout = Layout::default()
             .direction(Direction::Vertical)
             .constraints(vec![
@@ -55,7 +66,7 @@ impl<'a> MainLayoutUI<'a> {
                 Constraint::Max(1),
                 Constraint::Percentage(90),
             ])
-            .split(f.size());
+            .split(area);
         Layout::default()
             .direction(Direction::Horizontal)
             .constraints(vec![Constraint::Percentage(100)])
@@ -63,6 +74,11 @@ impl<'a> MainLayoutUI<'a> {
     }
 
     pub fn get_body_rect(&self, f: &mut Frame<'_>) -> Rc<[Rect]> {
+        self.body_rects(f.size())
+    }
+
+    /// The rects of the widgets by their `pos`, for a terminal of size `area`.
+    pub fn body_rects(&self, area: Rect) -> Rc<[Rect]> {
         let main_layout = Layout::default()
             .direction(Direction::Vertical)
             .constraints(vec![
@@ -70,14 +86,47 @@ impl<'a> MainLayoutUI<'a> {
                 Constraint::Max(1),
                 Constraint::Percentage(90),
             ])
-            .split(f.size());
-        Layout::default()
+            .split(area);
+        let columns = Layout::default()
             .direction(Direction::Horizontal)
             .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(main_layout[2])
+            .split(main_layout[2]);
+        if self.split {
+            let panes: Vec<Rc<[Rect]>> = columns
+                .iter()
+                .map(|column| {
+                    Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints(vec![Constraint::Percentage(35), Constraint::Percentage(65)])
+                        .split(*column)
+                })
+                .collect();
+            // deployments and events are not shown in the split view
+            return Rc::new([
+                panes[0][1],
+                panes[0][0],
+                Rect::default(),
+                Rect::default(),
+                panes[1][1],
+                panes[1][0],
+            ]);
+        }
+        let right_column = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Percentage(40),
+                Constraint::Percentage(25),
+                Constraint::Percentage(35),
+            ])
+            .split(columns[1]);
+        Rc::new([columns[0], right_column[0], right_column[1], right_column[2]])
     }
 
     pub fn get_header_rect(&self, line: usize, f: &mut Frame<'_>) -> Rc<[Rect]> {
+        self.header_rects(line, f.size())
+    }
+
+    pub fn header_rects(&self, line: usize, area: Rect) -> Rc<[Rect]> {
         let main_layout = Layout::default()
             .direction(Direction::Vertical)
             .constraints(vec![
@@ -85,10 +134,19 @@ impl<'a> MainLayoutUI<'a> {
                 Constraint::Max(1),
                 Constraint::Percentage(90),
             ])
-            .split(f.size());
+            .split(area);
+        // the first line has a cell for the actions in flight between the errors and the login
+        let constraints = match line {
+            0 => vec![
+                Constraint::Percentage(50),
+                Constraint::Percentage(30),
+                Constraint::Percentage(20),
+            ],
+            _ => vec![Constraint::Percentage(50), Constraint::Percentage(50)],
+        };
         Layout::default()
             .direction(Direction::Horizontal)
-            .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
+            .constraints(constraints)
             .split(main_layout[line])
     }
 }
@@ -99,6 +157,10 @@ pub struct UI<'a> {
     widgets: Option<Vec<Box<&'a dyn RenderWidget>>>,
     pub widget_fn: Option<fn(f: &mut Frame<'_>, layout: Rect)>,
     pub ui_transform: UITransform,
+    /// Title and text of a dialog drawn on top of the widgets.
+    pub popup: Option<(String, String)>,
+    /// Lines the text of the popup is scrolled down by.
+    pub popup_scroll: u16,
 }
 
 impl<'a> UI<'a> {
@@ -109,6 +171,8 @@ impl<'a> UI<'a> {
             widgets: None,
             widget_fn: None,
             ui_transform: UITransform::new(),
+            popup: None,
+            popup_scroll: 0,
         }
     }
     pub fn single(main_layout: &'a SingleLayoutUI) -> Self {
@@ -118,6 +182,8 @@ impl<'a> UI<'a> {
             widgets: None,
             widget_fn: None,
             ui_transform: UITransform::new(),
+            popup: None,
+            popup_scroll: 0,
         }
     }
 
@@ -133,9 +199,91 @@ impl<'a> UI<'a> {
             let rect = single_layout.get_body_rect(f);
             (self.widget_fn.unwrap())(f, rect);
         }
+        if let Some((title, text)) = &self.popup {
+            let rect = popup_rect(f.size(), text);
+            f.render_widget(Clear, rect);
+            f.render_widget(
+                Paragraph::new(text.clone())
+                    .block(Block::default().borders(Borders::all()).title(title.clone()))
+                    .wrap(Wrap { trim: true })
+                    .scroll((self.popup_scroll, 0)),
+                rect,
+            );
+        }
     }
 
     pub fn add_to_widgets(&mut self, widgets: Vec<Box<&'a dyn RenderWidget>>) {
         self.widgets = Some(widgets);
     }
 }
+
+/// The rect of a popup showing `text`, at least a fifth of the screen, higher when the text
+/// needs it.
+fn popup_rect(area: Rect, text: &str) -> Rect {
+    let lines = text.lines().count() as u16 + 2;
+    let percent_y = (lines * 100 / area.height.max(1) + 1).clamp(20, 100);
+    centered_rect(area, 50, percent_y)
+}
+
+/// The scroll offset that shows the end of `text` in its popup, with its lines wrapped.
+pub fn popup_end_scroll(area: Rect, text: &str) -> u16 {
+    let rect = popup_rect(area, text);
+    let width = rect.width.saturating_sub(2).max(1) as usize;
+    let rows: usize = text.lines().map(|line| wrapped_rows(line, width)).sum();
+    let end = rows.saturating_sub(rect.height.saturating_sub(2) as usize);
+    end.min(u16::MAX as usize) as u16
+}
+
+/// Rows `line` takes wrapped at word boundaries to `width`, long words broken up.
+fn wrapped_rows(line: &str, width: usize) -> usize {
+    let (mut rows, mut used) = (1, 0);
+    for word in line.split_whitespace() {
+        let len = word.chars().count();
+        if used == 0 {
+            used = len;
+        } else if used + 1 + len <= width {
+            used += 1 + len;
+        } else {
+            rows += 1;
+            used = len;
+        }
+        while used > width {
+            rows += 1;
+            used -= width;
+        }
+    }
+    rows
+}
+
+pub fn centered_rect(r: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+#[test]
+fn test_popup_end_scroll_counts_wrapped_rows() {
+    assert!(wrapped_rows("", 10) == 1);
+    assert!(wrapped_rows("stderr │ connection refused", 10) == 3);
+    assert!(wrapped_rows("abcdefghijklmnopqrstuvwxy", 10) == 3);
+    let area = Rect::new(0, 0, 40, 20);
+    // ten lines fit the 10 rows inside the popup, but each one wraps to 2 of its 18 columns
+    let text = ["a line long enough to wrap twice over"; 10].join("\n");
+    assert!(popup_rect(area, &text).height == 12);
+    assert!(popup_end_scroll(area, &text) == 10);
+    assert!(popup_end_scroll(area, "a line long enough to wrap twice over") == 0);
+}
@@ -1,15 +1,19 @@
 pub use std::{
-    sync::mpsc::{self, Receiver, Sender},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use log::{debug, error, trace};
 
 use crate::{
-    structs::{TUIError, UIState},
-    truncator::Truncatorix,
-    widgets::RenderWidget,
+    scheduler::frame_interval,
+    structs::{Direction2, KubeEnv, UIState},
+    widget_state::{AuthStatus, HeaderState, InFlightAction, PickerState},
+    widgets::{
+        refresh_namespace_picker, scroll_log_view, scroll_view, select_row_at, selected_row,
+        BodyWidget, CliWidgetId, RenderWidget,
+    },
     Store, TUIAction, TUIEvent,
 };
 
@@ -18,7 +22,6 @@ pub struct WidgetDataStore<'a> {
     store: &'a mut Store,
     store_tx: Sender<Store>,
     action_tx: Sender<TUIAction>,
-    truncator: Box<dyn Truncatorix>,
 }
 
 impl<'a> WidgetDataStore<'a> {
@@ -27,128 +30,472 @@ impl<'a> WidgetDataStore<'a> {
         store: &'a mut Store,
         store_tx: Sender<Store>,
         action_tx: Sender<TUIAction>,
-        truncator: Box<dyn Truncatorix>,
     ) -> Self {
         WidgetDataStore {
             event_rx,
             store,
             store_tx,
             action_tx,
-            truncator,
         }
     }
 
-    fn start_truncator(&mut self) {
-        self.truncator.start();
-    }
-
     pub fn start(&mut self, event_handlers: Vec<fn(&TUIEvent, &mut Store) -> Option<()>>) {
-        self.start_truncator();
         self.send();
-        while let Ok(event) = self.event_rx.recv() {
-            trace!("handling event: {:?}", event);
-            let action_tx_clone = self.action_tx.clone();
-            match event {
-                TUIEvent::RequestEnvChange => {
-                    self.store.env_change_possible = true;
+        // events arriving within a frame are all handled before the next snapshot, so a busy
+        // stream costs one copy of the store per frame instead of one per line
+        let frame = frame_interval();
+        let mut last_sent = Instant::now();
+        let mut changed = false;
+        loop {
+            let event = if changed {
+                match self
+                    .event_rx
+                    .recv_timeout(frame.saturating_sub(last_sent.elapsed()))
+                {
+                    Ok(event) => Some(event),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            } else {
+                match self.event_rx.recv() {
+                    Ok(event) => Some(event),
+                    Err(_) => break,
+                }
+            };
+            if let Some(event) = event {
+                self.handle_event(event, &event_handlers);
+                changed = true;
+            }
+            if changed && last_sent.elapsed() >= frame {
+                self.send();
+                last_sent = Instant::now();
+                changed = false;
+            }
+        }
+        if changed {
+            self.send();
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        event: TUIEvent,
+        event_handlers: &[fn(&TUIEvent, &mut Store) -> Option<()>],
+    ) {
+        trace!("handling event: {:?}", event);
+        let action_tx_clone = self.action_tx.clone();
+        match event {
+            TUIEvent::RequestEnvChange => {
+                self.store.env_change_possible = true;
+            }
+            TUIEvent::EnvChange(env) => {
+                let namespace = self.store.recent_namespaces.current(env);
+                action_tx_clone
+                    .send(TUIAction::SetNamespace(env, namespace.clone()))
+                    .unwrap();
+                action_tx_clone.send(TUIAction::ChangeEnv(env)).unwrap();
+                self.store.env_change_possible = false;
+                self.store.kube_env = Some(env);
+                let header = self.header_mut();
+                header.env = Some(env);
+                header.namespace = Some(namespace);
+                self.label_split_panes();
+            }
+            TUIEvent::Error(report) => self.header_mut().report(report),
+            TUIEvent::ClearError => self.header_mut().error = None,
+            TUIEvent::CheckConnectivity => {
+                self.store.request_login = false;
+                action_tx_clone.send(TUIAction::CheckConnectivity).unwrap();
+            }
+            TUIEvent::RequestLoginStart => {
+                self.store.request_login = true;
+            }
+            TUIEvent::RequestLoginStop => {
+                self.store.request_login = false;
+            }
+            TUIEvent::NeedsLogin => {
+                self.store.ui_state = UIState::LoggingIn;
+                self.action_tx.send(TUIAction::LogIn).unwrap();
+            }
+            TUIEvent::IsLoggedIn => {
+                debug!("logged in");
+                self.store.logged_in = true;
+                if let Some(login_widget) = self.store.login_widget.as_mut() {
+                    login_widget.clear_state();
                 }
-                TUIEvent::EnvChange(env) => {
+                self.header_mut().auth = AuthStatus::LoggedIn;
+                self.action_tx.send(TUIAction::CheckConnectivity).unwrap();
+            }
+            TUIEvent::IsConnected => {
+                self.store.logged_in = true;
+                if let Some(login_widget) = self.store.login_widget.as_mut() {
+                    login_widget.clear_state();
+                }
+                self.header_mut().auth = AuthStatus::Connected;
+            }
+            TUIEvent::DescribePod => {
+                if let Some(pod) = selected_row(self.store.pods_widget.as_ref().unwrap().get_data())
+                {
+                    action_tx_clone.send(TUIAction::DescribePod(pod)).unwrap();
+                }
+            }
+            TUIEvent::RequestRolloutUndo => {
+                self.store.pending_rollout_undo =
+                    selected_row(self.store.deployments_widget.as_ref().unwrap().get_data());
+            }
+            TUIEvent::ConfirmRolloutUndo => {
+                if let Some(deployment) = self.store.pending_rollout_undo.take() {
                     action_tx_clone
-                        .send(TUIAction::ChangeEnv(env.clone()))
+                        .send(TUIAction::RolloutUndo(deployment))
                         .unwrap();
-                    self.store.env_change_possible = false;
-                    self.store.header_widget.as_mut().unwrap().set_data(
-                        "kube_info".to_string(),
-                        vec![format!("{:?}", env).to_string()],
-                    );
                 }
-                TUIEvent::Error(error) => match error {
-                    TUIError::VPN => {
-                        self.store
-                            .header_widget
-                            .as_mut()
-                            .unwrap()
-                            .set_data("error".to_string(), vec!["Uhm... VPN on ?".to_string()]);
-                    }
-                    TUIError::KEY(error) | TUIError::API(error) => {
-                        self.store
-                            .header_widget
-                            .as_mut()
-                            .unwrap()
-                            .set_data("error".to_string(), vec![error]);
-                    }
-                },
-                TUIEvent::ClearError => {
-                    if let Some(header_widget) = self.store.header_widget.as_mut() {
-                        header_widget.clear_text_data("error".to_string());
+            }
+            TUIEvent::CancelRolloutUndo => {
+                self.store.pending_rollout_undo = None;
+            }
+            TUIEvent::ToggleSplit => match self.store.split_env.take() {
+                Some(env) => {
+                    action_tx_clone.send(TUIAction::CloseSplit).unwrap();
+                    action_tx_clone
+                        .send(TUIAction::CancelRefresh(Box::new(TUIAction::GetSplitPods(
+                            env,
+                        ))))
+                        .unwrap();
+                    for widget in [
+                        self.store.split_logs_widget.as_mut(),
+                        self.store.split_pods_widget.as_mut(),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    {
+                        widget.clear_state();
                     }
+                    self.header_mut().split_env = None;
+                    self.label_split_panes();
+                    self.move_focus(Direction2::Up);
                 }
-                TUIEvent::CheckConnectivity => {
-                    self.store.request_login = false;
-                    action_tx_clone.send(TUIAction::CheckConnectivity).unwrap();
+                None => {
+                    if let Some(env) = self.store.kube_env.map(|env| env.other()) {
+                        self.store.split_env = Some(env);
+                        action_tx_clone
+                            .send(TUIAction::SetNamespace(
+                                env,
+                                self.store.recent_namespaces.current(env),
+                            ))
+                            .unwrap();
+                        action_tx_clone.send(TUIAction::OpenSplit(env)).unwrap();
+                        action_tx_clone
+                            .send(TUIAction::Refresh(
+                                Box::new(TUIAction::GetSplitPods(env)),
+                                Duration::from_secs(10),
+                            ))
+                            .unwrap();
+                        self.header_mut().split_env = Some(env);
+                        self.label_split_panes();
+                        self.move_focus(Direction2::Up);
+                    }
                 }
-                TUIEvent::RequestLoginStart => {
-                    self.store.request_login = true;
+            },
+            TUIEvent::OpenNamespacePicker => {
+                self.store.namespace_picker_open = true;
+                if let Some(namespaces_widget) = self.store.namespaces_widget.as_mut() {
+                    *namespaces_widget.state_mut::<PickerState>() = PickerState::default();
                 }
-                TUIEvent::RequestLoginStop => {
-                    self.store.request_login = false;
+                refresh_namespace_picker(self.store);
+                action_tx_clone.send(TUIAction::GetNamespaces).unwrap();
+            }
+            TUIEvent::CloseNamespacePicker => self.close_namespace_picker(),
+            TUIEvent::SelectNamespace => {
+                let selected = self
+                    .store
+                    .namespaces_widget
+                    .as_ref()
+                    .and_then(|widget| selected_row(widget.get_data()));
+                if let (Some(env), Some(namespace)) = (self.store.kube_env, selected) {
+                    self.change_namespace(env, namespace);
                 }
-                TUIEvent::NeedsLogin => {
-                    self.store.ui_state = UIState::LoggingIn;
-                    self.action_tx.send(TUIAction::LogIn).unwrap();
+                self.close_namespace_picker();
+            }
+            TUIEvent::ExportLogs => {
+                let export = self
+                    .store
+                    .logs_widget
+                    .as_ref()
+                    .unwrap()
+                    .current_log()
+                    .export();
+                action_tx_clone.send(TUIAction::ExportLogs(export)).unwrap();
+            }
+            TUIEvent::RunAction(action) => action_tx_clone.send(action).unwrap(),
+            TUIEvent::Quit => self.store.quit = true,
+            TUIEvent::ToggleHelp => {
+                self.store.help_open = !self.store.help_open;
+                self.header_mut().hint = None;
+            }
+            TUIEvent::ToggleErrors => {
+                self.store.errors_open = !self.store.errors_open;
+                let header = self.header_mut();
+                header.unread_errors = 0;
+                header.errors_scroll = 0;
+            }
+            TUIEvent::ScrollErrors(lines, end) => {
+                let header = self.header_mut();
+                header.errors_scroll =
+                    (header.errors_scroll as i64 + lines as i64).clamp(0, end as i64) as u16;
+            }
+            TUIEvent::ToggleProcesses => {
+                self.store.processes_open = !self.store.processes_open;
+                self.header_mut().hint = None;
+            }
+            TUIEvent::ActionStarted(id, label) => {
+                self.header_mut().actions.push(InFlightAction {
+                    id,
+                    label,
+                    started: Instant::now(),
+                });
+            }
+            TUIEvent::ActionFinished(id) => {
+                let header = self.header_mut();
+                header.actions.retain(|action| action.id != id);
+                header.selected_action = header
+                    .selected_action
+                    .min(header.actions.len().saturating_sub(1));
+            }
+            TUIEvent::SelectNextAction => {
+                let header = self.header_mut();
+                if !header.actions.is_empty() {
+                    header.selected_action = (header.selected_action + 1) % header.actions.len();
                 }
-                TUIEvent::IsLoggedIn => {
-                    debug!("logged in");
-                    self.store.logged_in = true;
-                    if let Some(login_widget) = self.store.login_widget.as_mut() {
-                        login_widget.clear_text_data("logs".to_string());
-                    }
-                    if let Some(header_widget) = self.store.header_widget.as_mut() {
-                        header_widget.set_data("logged in".to_string(), vec![true.to_string()]);
-                    }
-                    self.action_tx.send(TUIAction::CheckConnectivity).unwrap();
+            }
+            TUIEvent::CancelSelectedAction => {
+                let header = self.header_mut();
+                if let Some(action) = header.actions.get(header.selected_action) {
+                    action_tx_clone
+                        .send(TUIAction::CancelAction(action.id))
+                        .unwrap();
                 }
-                TUIEvent::IsConnected => {
-                    self.store.logged_in = true;
-                    if let Some(login_widget) = self.store.login_widget.as_mut() {
-                        login_widget.clear_text_data("logs".to_string());
-                    }
-                    if let Some(header_widget) = self.store.header_widget.as_mut() {
-                        header_widget.set_data("logged in".to_string(), vec![true.to_string()]);
-                        header_widget
-                            .set_data("login_info".to_string(), vec!["LOGGED IN".to_string()]);
+            }
+            TUIEvent::ShowHint(hint) => self.header_mut().hint = Some(hint),
+            TUIEvent::FocusWidget(id) => self.focus_widget(&id),
+            TUIEvent::ScrollWidget(id, lines, follow) => {
+                if let Some(widget) = self.store.body_widget_mut(&id) {
+                    match id {
+                        CliWidgetId::DescribePod => scroll_view(widget, lines, follow),
+                        _ => scroll_log_view(widget, lines, follow),
                     }
                 }
-                TUIEvent::DisplayLoginCode(code) => {
-                    self.store.login_code = Some(code);
+            }
+            TUIEvent::SelectRow(id, line) => {
+                if let Some(widget) = self.store.body_widget_mut(&id) {
+                    select_row_at(widget, line);
                 }
-                event => {
-                    let mut event_handlers = event_handlers.iter();
-                    let mut b = Some(());
-                    while let Some(()) = b {
-                        if let Some(next_handler) = event_handlers.next() {
-                            b = next_handler(&event, &mut self.store)
-                        }
-                    }
+            }
+            TUIEvent::ClearHint => self.header_mut().hint = None,
+            TUIEvent::DisplayLoginCode(code) => {
+                self.store.login_code = Some(code);
+            }
+            event => {
+                let consumed = event_handlers
+                    .iter()
+                    .any(|event_handler| event_handler(&event, self.store).is_none());
+                if let (false, TUIEvent::Navigate(direction)) = (consumed, event) {
+                    self.move_focus(direction);
                 }
             }
-            if let Some(()) = self.truncator.poll() {
-                self.truncator.truncate(self.store)
+        }
+    }
+
+    /// Names the env of each pane in its title while the panes are split, so they can be told apart.
+    fn label_split_panes(&mut self) {
+        let store = &mut *self.store;
+        let (env, split_env) = match store.split_env {
+            Some(split_env) => (store.kube_env, Some(split_env)),
+            None => (None, None),
+        };
+        for (widget, env) in [
+            (store.logs_widget.as_mut(), env),
+            (store.pods_widget.as_mut(), env),
+            (store.split_logs_widget.as_mut(), split_env),
+            (store.split_pods_widget.as_mut(), split_env),
+        ] {
+            if let Some(widget) = widget {
+                widget.get_widget_mut().env = env;
+            }
+        }
+    }
+
+    fn close_namespace_picker(&mut self) {
+        self.store.namespace_picker_open = false;
+        if let Some(namespaces_widget) = self.store.namespaces_widget.as_mut() {
+            namespaces_widget.clear_state();
+        }
+    }
+
+    fn header_mut(&mut self) -> &mut HeaderState {
+        self.store
+            .header_widget
+            .as_mut()
+            .unwrap()
+            .state_mut::<HeaderState>()
+    }
+
+    /// Remembers `namespace` for `env` and re-targets the namespaced widgets to it.
+    fn change_namespace(&mut self, env: KubeEnv, namespace: String) {
+        self.store.recent_namespaces.push(env, &namespace);
+        self.store.recent_namespaces.save();
+        self.header_mut().namespace = Some(namespace.clone());
+        for widget in [
+            self.store.logs_widget.as_mut(),
+            self.store.pods_widget.as_mut(),
+            self.store.events_widget.as_mut(),
+            self.store.deployments_widget.as_mut(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            widget.clear_state();
+        }
+        for action in [
+            TUIAction::SetNamespace(env, namespace),
+            TUIAction::GetPods,
+            TUIAction::GetLogs,
+            TUIAction::GetEvents,
+            TUIAction::GetDeployments,
+        ] {
+            self.action_tx.send(action).unwrap();
+        }
+    }
+
+    /// Moves the focus to the previous or next visible body widget on `h`/`l`.
+    /// Any other direction only moves the focus away from hidden widgets.
+    fn move_focus(&mut self, direction: Direction2) {
+        let store = &mut *self.store;
+        let (visible, hidden) = if store.split_env.is_some() {
+            (
+                [
+                    store.pods_widget.as_mut(),
+                    store.logs_widget.as_mut(),
+                    store.split_pods_widget.as_mut(),
+                    store.split_logs_widget.as_mut(),
+                ],
+                [
+                    store.deployments_widget.as_mut(),
+                    store.events_widget.as_mut(),
+                ],
+            )
+        } else {
+            (
+                [
+                    store.logs_widget.as_mut(),
+                    store.pods_widget.as_mut(),
+                    store.deployments_widget.as_mut(),
+                    store.events_widget.as_mut(),
+                ],
+                [
+                    store.split_pods_widget.as_mut(),
+                    store.split_logs_widget.as_mut(),
+                ],
+            )
+        };
+        for widget in hidden.into_iter().flatten() {
+            widget.get_widget_mut().is_selected = false;
+        }
+        let mut widgets: Vec<&mut BodyWidget> = visible.into_iter().flatten().collect();
+        let focused = widgets
+            .iter()
+            .position(|widget| widget.get_widget().is_selected)
+            .unwrap_or(0);
+        let next = match direction {
+            Direction2::Left => (focused + widgets.len() - 1) % widgets.len(),
+            Direction2::Right => (focused + 1) % widgets.len(),
+            _ => focused,
+        };
+        for (index, widget) in widgets.iter_mut().enumerate() {
+            widget.get_widget_mut().is_selected = index == next;
+        }
+    }
+
+    fn focus_widget(&mut self, id: &CliWidgetId) {
+        let visible: Vec<CliWidgetId> = self
+            .store
+            .visible_widgets()
+            .iter()
+            .map(|widget| widget.get_widget().id.clone())
+            .collect();
+        if !visible.contains(id) {
+            return;
+        }
+        for visible_id in visible {
+            if let Some(widget) = self.store.body_widget_mut(&visible_id) {
+                widget.get_widget_mut().is_selected = visible_id == *id;
             }
-            self.send()
         }
     }
 
     fn send(&self) {
         match self.store_tx.send(self.store.clone()) {
-            Ok(_) => trace!("sending store {:?}", self.store.clone()),
+            Ok(_) => trace!("sending store {:?}", self.store),
             Err(err) => error!("Error sending to store_tx: {}", err),
         }
     }
 }
 
+/// The store once the events sent so far are handled, waiting for snapshots to stop coming.
+#[cfg(test)]
+fn settled_store(store_rx: &Receiver<Store>) -> Store {
+    let mut store = store_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    while let Ok(newer) = store_rx.recv_timeout(frame_interval() * 3) {
+        store = newer;
+    }
+    store
+}
+
+/// Starts a store of fresh widgets handling events with `handlers`, returns where its events go
+/// in and where its actions and snapshots come out.
+#[cfg(test)]
+fn spawn_store(
+    handlers: Vec<fn(&TUIEvent, &mut Store) -> Option<()>>,
+) -> (Sender<TUIEvent>, Receiver<TUIAction>, Receiver<Store>) {
+    spawn_store_with(handlers, |_| {})
+}
+
+/// Like `spawn_store`, with `setup` changing the store before it starts.
+#[cfg(test)]
+fn spawn_store_with(
+    handlers: Vec<fn(&TUIEvent, &mut Store) -> Option<()>>,
+    setup: fn(&mut Store),
+) -> (Sender<TUIEvent>, Receiver<TUIAction>, Receiver<Store>) {
+    use crate::widgets::*;
+
+    crate::init_logging().unwrap();
+    let (event_tx, event_rx) = mpsc::channel();
+    let (action_tx, action_rx) = mpsc::channel();
+    let (store_tx, store_rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut store = Store::new(
+            create_header_widget_data().get_widget(),
+            create_login_widget_data().get_widget(),
+            create_logs_widget_data().get_widget(),
+            create_pods_widget_data().get_widget(),
+            create_tail_widget_data().get_widget(),
+        );
+        store.events_widget = Some(create_events_widget_data().get_widget());
+        store.describe_widget = Some(create_describe_widget_data().get_widget());
+        store.deployments_widget = Some(create_deployments_widget_data().get_widget());
+        store.split_logs_widget = Some(create_split_logs_widget_data().get_widget());
+        store.split_pods_widget = Some(create_split_pods_widget_data().get_widget());
+        store.namespaces_widget = Some(create_namespaces_widget_data().get_widget());
+        store.palette_widget = Some(create_palette_widget_data().get_widget());
+        setup(&mut store);
+        let mut widget_data_store = WidgetDataStore::new(event_rx, &mut store, store_tx, action_tx);
+        widget_data_store.start(handlers)
+    });
+    (event_tx, action_rx, store_rx)
+}
+
 #[test]
 fn test_error_events() {
+    use crate::{errors::ErrorReport, structs::TUIError};
     crate::init_logging().unwrap();
     let (event_tx, event_rx): (Sender<TUIEvent>, Receiver<TUIEvent>) = mpsc::channel();
     let (action_tx, _): (Sender<TUIAction>, Receiver<TUIAction>) = mpsc::channel();
@@ -167,84 +514,119 @@ fn test_error_events() {
             pods_widget_data.get_widget().clone(),
             pods_widget_data.get_widget().clone(),
         );
-        let mut widget_data_store = WidgetDataStore::new(
-            event_rx,
-            &mut store,
-            store_tx,
-            action_tx,
-            Box::new(crate::truncator::NoopTruncator::new()),
-        );
+        let mut widget_data_store = WidgetDataStore::new(event_rx, &mut store, store_tx, action_tx);
         widget_data_store.start(vec![
             login_widget_data.get_event_handler(),
             logs_widget_data.get_event_handler(),
             pods_widget_data.get_event_handler(),
         ])
     });
-    if let Ok(updated_store) = store_rx.recv_timeout(Duration::from_millis(10)) {
-        assert!(
-            updated_store
-                .clone()
-                .header_widget
-                .unwrap()
-                .get_data()
-                .data
-                .get("error")
-                == None,
-            "store was: {:?}",
-            updated_store
-        )
-    }
+    let updated_store = settled_store(&store_rx);
+    assert!(
+        updated_store
+            .clone()
+            .header_widget
+            .unwrap()
+            .state::<HeaderState>()
+            .error
+            == None,
+        "store was: {:?}",
+        updated_store
+    );
 
-    event_tx.send(TUIEvent::Error(TUIError::VPN)).unwrap();
-
-    if let Ok(updated_store) = store_rx.recv_timeout(Duration::from_millis(10)) {
-        assert!(
-            updated_store
-                .clone()
-                .header_widget
-                .unwrap()
-                .get_data()
-                .data
-                .get("error")
-                == Some(Some(vec!["Uhm... VPN on ?".to_string()])).as_ref(),
-            "store was: {:?}",
-            updated_store
-        )
-    }
+    event_tx
+        .send(TUIEvent::Error(ErrorReport::new("test", TUIError::VPN)))
+        .unwrap();
 
-    if let Ok(updated_store) = store_rx.recv_timeout(Duration::from_millis(10)) {
-        assert!(
-            updated_store
-                .clone()
-                .header_widget
-                .unwrap()
-                .get_data()
-                .data
-                .get("error")
-                == None,
-            "store was: {:?}",
-            updated_store
-        )
-    }
+    let updated_store = settled_store(&store_rx);
+    assert!(
+        updated_store
+            .clone()
+            .header_widget
+            .unwrap()
+            .state::<HeaderState>()
+            .error
+            == Some("Uhm... VPN on ?".to_string()),
+        "store was: {:?}",
+        updated_store
+    );
+
+    event_tx.send(TUIEvent::ClearError).unwrap();
+
+    let updated_store = settled_store(&store_rx);
+    assert!(
+        updated_store
+            .clone()
+            .header_widget
+            .unwrap()
+            .state::<HeaderState>()
+            .error
+            == None,
+        "store was: {:?}",
+        updated_store
+    );
 
     event_tx
-        .send(TUIEvent::Error(TUIError::API("this errored".to_string())))
+        .send(TUIEvent::Error(ErrorReport::new(
+            "test",
+            TUIError::API("this errored".to_string()),
+        )))
         .unwrap();
 
-    if let Ok(updated_store) = store_rx.recv_timeout(Duration::from_millis(10)) {
-        assert!(
-            updated_store
-                .clone()
-                .header_widget
-                .unwrap()
-                .get_data()
-                .data
-                .get("error")
-                == Some(Some(vec!["this errored".to_string()])).as_ref(),
-            "store was: {:?}",
-            updated_store
-        )
+    let updated_store = settled_store(&store_rx);
+    assert!(
+        updated_store
+            .clone()
+            .header_widget
+            .unwrap()
+            .state::<HeaderState>()
+            .error
+            == Some("this errored".to_string()),
+        "store was: {:?}",
+        updated_store
+    );
+
+    let header = updated_store
+        .header_widget
+        .unwrap()
+        .state::<HeaderState>()
+        .clone();
+    assert!(header.errors.len() == 2 && header.unread_errors == 2);
+    assert!(header.errors[1].source == "test");
+
+    // a stream printing the same error again folds into one entry
+    for _ in 0..3 {
+        event_tx
+            .send(TUIEvent::Error(ErrorReport::new(
+                "test",
+                TUIError::API("this errored".to_string()),
+            )))
+            .unwrap();
     }
+    let updated_store = settled_store(&store_rx);
+    let header = updated_store
+        .header_widget
+        .unwrap()
+        .state::<HeaderState>()
+        .clone();
+    assert!(header.errors.len() == 2 && header.errors[1].count == 4);
+    assert!(header.unread_errors == 2);
+
+    event_tx.send(TUIEvent::ToggleErrors).unwrap();
+    event_tx.send(TUIEvent::ScrollErrors(100, 3)).unwrap();
+    let updated_store = settled_store(&store_rx);
+    assert!(updated_store.errors_open);
+    let header = updated_store
+        .header_widget
+        .unwrap()
+        .state::<HeaderState>()
+        .clone();
+    assert!(header.unread_errors == 0);
+    assert!(
+        header.errors_scroll == 3,
+        "scroll was {}",
+        header.errors_scroll
+    );
 }
 
 #[test]
@@ -267,26 +649,19 @@ fn test_check_connectivity_event() {
             pods_widget_data.get_widget().clone(),
             pods_widget_data.get_widget().clone(),
         );
-        let mut widget_data_store = WidgetDataStore::new(
-            event_rx,
-            &mut store,
-            store_tx,
-            action_tx,
-            Box::new(crate::truncator::NoopTruncator::new()),
-        );
+        let mut widget_data_store = WidgetDataStore::new(event_rx, &mut store, store_tx, action_tx);
         widget_data_store.start(vec![
             login_widget_data.get_event_handler(),
             logs_widget_data.get_event_handler(),
             pods_widget_data.get_event_handler(),
         ])
     });
-    if let Ok(updated_store) = store_rx.recv_timeout(Duration::from_millis(10)) {
-        assert!(
-            !updated_store.request_login,
-            "store was: {:?}",
-            updated_store
-        )
-    }
+    let updated_store = settled_store(&store_rx);
+    assert!(
+        !updated_store.request_login,
+        "store was: {:?}",
+        updated_store
+    );
 
     event_tx.send(TUIEvent::CheckConnectivity).unwrap();
 
@@ -321,22 +696,15 @@ fn test_login_event() {
             pods_widget_data.get_widget().clone(),
             pods_widget_data.get_widget().clone(),
         );
-        let mut widget_data_store = WidgetDataStore::new(
-            event_rx,
-            &mut store,
-            store_tx,
-            action_tx,
-            Box::new(crate::truncator::NoopTruncator::new()),
-        );
+        let mut widget_data_store = WidgetDataStore::new(event_rx, &mut store, store_tx, action_tx);
         widget_data_store.start(vec![
             login_widget_data.get_event_handler(),
             logs_widget_data.get_event_handler(),
             pods_widget_data.get_event_handler(),
         ])
     });
-    if let Ok(updated_store) = store_rx.recv_timeout(Duration::from_millis(10)) {
-        assert!(!updated_store.logged_in, "store was: {:?}", updated_store)
-    }
+    let updated_store = settled_store(&store_rx);
+    assert!(!updated_store.logged_in, "store was: {:?}", updated_store);
 
     event_tx.send(TUIEvent::NeedsLogin).unwrap();
 
@@ -371,73 +739,744 @@ fn test_add_log_event() {
             pods_widget_data.get_widget().clone(),
             pods_widget_data.get_widget().clone(),
         );
-        let mut widget_data_store = WidgetDataStore::new(
-            event_rx,
-            &mut store,
-            store_tx,
-            action_tx,
-            Box::new(crate::truncator::NoopTruncator::new()),
-        );
+        let mut widget_data_store = WidgetDataStore::new(event_rx, &mut store, store_tx, action_tx);
         widget_data_store.start(vec![
             login_widget_data.get_event_handler(),
             logs_widget_data.get_event_handler(),
             pods_widget_data.get_event_handler(),
         ])
     });
-    if let Ok(updated_store) = store_rx.recv_timeout(Duration::from_millis(10)) {
-        assert!(
-            updated_store
-                .clone()
-                .logs_widget
-                .unwrap()
-                .get_data()
-                .data
-                .get("logs")
-                == None,
-            "store was: {:?}",
-            updated_store
-        )
-    }
+    let updated_store = settled_store(&store_rx);
+    assert!(
+        updated_store
+            .clone()
+            .logs_widget
+            .unwrap()
+            .get_data()
+            .state
+            .lines()
+            == None,
+        "store was: {:?}",
+        updated_store
+    );
 
     event_tx
-        .send(TUIEvent::AddLog("this is a new line\n".to_string()))
+        .send(TUIEvent::AddLog(0, "this is a new line\n".to_string()))
         .unwrap();
 
-    if let Ok(updated_store) = store_rx.recv_timeout(Duration::from_millis(10)) {
-        assert!(
-            updated_store
-                .clone()
-                .logs_widget
-                .unwrap()
-                .get_data()
-                .data
-                .get("logs")
-                == Some(Some(vec!["this is a new line\n".to_string()])).as_ref(),
-            "store was: {:?}",
-            updated_store
-        )
-    }
+    let updated_store = settled_store(&store_rx);
+    assert!(
+        updated_store
+            .clone()
+            .logs_widget
+            .unwrap()
+            .get_data()
+            .state
+            .lines()
+            == Some(vec!["this is a new line\n".to_string()]),
+        "store was: {:?}",
+        updated_store
+    );
+
+    event_tx
+        .send(TUIEvent::AddLog(0, "and some extra.".to_string()))
+        .unwrap();
+
+    let updated_store = settled_store(&store_rx);
+    assert!(
+        updated_store
+            .clone()
+            .logs_widget
+            .unwrap()
+            .get_data()
+            .state
+            .lines()
+            == Some(vec![
+                "this is a new line\n".to_string(),
+                "and some extra.".to_string()
+            ]),
+        "store was: {:?}",
+        updated_store
+    );
+}
+
+#[test]
+fn test_events_filter_follows_selected_row() {
+    let (event_tx, _, store_rx) = spawn_store(vec![
+        crate::widgets::create_login_widget_data().get_event_handler(),
+        crate::widgets::create_logs_widget_data().get_event_handler(),
+        crate::widgets::create_pods_widget_data().get_event_handler(),
+        crate::widgets::create_events_widget_data().get_event_handler(),
+    ]);
+
+    event_tx
+        .send(TUIEvent::AddPods(
+            "NAME    READY   STATUS    RESTARTS   AGE\npod-a   1/1     Running   0          1d\npod-b   0/1     Pending   0          1m\n"
+                .to_string(),
+        ))
+        .unwrap();
+    event_tx.send(TUIEvent::ToggleEventsFilter).unwrap();
+    event_tx
+        .send(TUIEvent::Navigate(crate::structs::Direction2::Down))
+        .unwrap();
+
+    let updated_store = settled_store(&store_rx);
+    let events_widget = updated_store.events_widget.as_ref().unwrap();
+    let pods_widget = updated_store.pods_widget.as_ref().unwrap();
+    assert!(
+        crate::widgets::selected_row(pods_widget.get_data()) == Some("pod-b".to_string()),
+        "store was: {:?}",
+        updated_store
+    );
+    assert!(
+        events_widget
+            .state::<crate::widget_state::EventsState>()
+            .pod
+            == Some("pod-b".to_string()),
+        "store was: {:?}",
+        updated_store
+    );
+
+    event_tx.send(TUIEvent::ToggleEventsFilter).unwrap();
+    let updated_store = settled_store(&store_rx);
+    let events_widget = updated_store.events_widget.as_ref().unwrap();
+    assert!(
+        events_widget
+            .state::<crate::widget_state::EventsState>()
+            .pod
+            .is_none(),
+        "store was: {:?}",
+        updated_store
+    );
+}
+
+#[test]
+fn test_pod_description_sections_collapse() {
+    let (event_tx, _, store_rx) = spawn_store(vec![
+        crate::widgets::create_describe_widget_data().get_event_handler(),
+        crate::widgets::create_pods_widget_data().get_event_handler(),
+    ]);
 
     event_tx
-        .send(TUIEvent::AddLog("and some extra.".to_string()))
-        .unwrap();
-
-    if let Ok(updated_store) = store_rx.recv_timeout(Duration::from_millis(10)) {
-        assert!(
-            updated_store
-                .clone()
-                .logs_widget
-                .unwrap()
-                .get_data()
-                .data
-                .get("logs")
-                == Some(Some(vec![
-                    "this is a new line\n".to_string(),
-                    "and some extra.".to_string()
-                ]))
-                .as_ref(),
-            "store was: {:?}",
-            updated_store
-        )
+        .send(TUIEvent::AddPodDescription(
+            "Name:         pod-a\nNamespace:    dev\nContainers:\n  app:\n    Image:  app:1.2\nConditions:\n  Type   Status\n  Ready  True\nEvents:       <none>\n"
+                .to_string(),
+        ))
+        .unwrap();
+    event_tx.send(TUIEvent::NextDescribeSection).unwrap();
+    event_tx.send(TUIEvent::ToggleDescribeSection).unwrap();
+
+    let updated_store = settled_store(&store_rx);
+    let describe_widget = updated_store.describe_widget.as_ref().unwrap();
+    assert!(
+        describe_widget.get_data().state.lines()
+            == Some(vec![
+                "Name:         pod-a".to_string(),
+                "Namespace:    dev".to_string(),
+                "▸ Containers: (2 lines)".to_string(),
+                "▾ Conditions:".to_string(),
+                "  Type   Status".to_string(),
+                "  Ready  True".to_string(),
+                "Events:       <none>".to_string(),
+            ]),
+        "store was: {:?}",
+        updated_store
+    );
+
+    // scrolling stops where the end of the description is shown
+    for lines in [2, 5, -1] {
+        event_tx
+            .send(TUIEvent::ScrollWidget(CliWidgetId::DescribePod, lines, 3))
+            .unwrap();
     }
+    event_tx
+        .send(TUIEvent::Navigate(crate::structs::Direction2::Down))
+        .unwrap();
+    let updated_store = settled_store(&store_rx);
+    let describe_widget = updated_store.describe_widget.as_ref().unwrap();
+    assert!(describe_widget.get_widget().scroll == Some(2));
+
+    event_tx.send(TUIEvent::ClosePodDescription).unwrap();
+    let updated_store = settled_store(&store_rx);
+    let describe_widget = updated_store.describe_widget.as_ref().unwrap();
+    assert!(
+        describe_widget.get_data().state.lines().is_none(),
+        "store was: {:?}",
+        updated_store
+    );
+}
+
+#[test]
+fn test_rollout_undo_needs_confirmation() {
+    let (event_tx, action_rx, store_rx) = spawn_store(vec![
+        crate::widgets::create_pods_widget_data().get_event_handler(),
+        crate::widgets::create_deployments_widget_data().get_event_handler(),
+    ]);
+
+    event_tx
+        .send(TUIEvent::AddDeployments(
+            "NAME   DESIRED   READY   UPDATED   AVAILABLE   GENERATION   OBSERVED   IMAGES\napi    2         2       2         2           3            3          repo/api:1.0\nweb    2         1       1         1           4            4          repo/web:2.1\n"
+                .to_string(),
+        ))
+        .unwrap();
+    event_tx
+        .send(TUIEvent::Navigate(crate::structs::Direction2::Right))
+        .unwrap();
+    event_tx
+        .send(TUIEvent::Navigate(crate::structs::Direction2::Down))
+        .unwrap();
+    event_tx.send(TUIEvent::RequestRolloutUndo).unwrap();
+
+    let updated_store = settled_store(&store_rx);
+    assert!(
+        updated_store
+            .deployments_widget
+            .clone()
+            .unwrap()
+            .get_widget()
+            .is_selected,
+        "store was: {:?}",
+        updated_store
+    );
+    assert!(
+        updated_store.pending_rollout_undo == Some("web".to_string()),
+        "store was: {:?}",
+        updated_store
+    );
+    assert!(action_rx.try_recv().is_err());
+
+    event_tx.send(TUIEvent::ConfirmRolloutUndo).unwrap();
+    let action = action_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert!(
+        action == TUIAction::RolloutUndo("web".to_string()),
+        "action was: {:?}",
+        action
+    );
+}
+
+#[test]
+fn test_top_samples_are_kept_per_pod() {
+    let (event_tx, _, store_rx) = spawn_store(vec![
+        crate::widgets::create_pods_widget_data().get_event_handler()
+    ]);
+
+    event_tx
+        .send(TUIEvent::AddPodLimits(
+            "NAME    MEMORY      CONTAINERS\npod-a   512Mi,1Gi   app,proxy\npod-b   <none>      app\npod-c   512Mi       app,proxy\n"
+                .to_string(),
+        ))
+        .unwrap();
+    event_tx
+        .send(TUIEvent::AddTop(
+            "pod-a   250m   1000Mi\npod-b   1   64Mi\npod-c   10m   500Mi\n".to_string(),
+        ))
+        .unwrap();
+    event_tx
+        .send(TUIEvent::AddTop(
+            "pod-a   300m   1200Mi\npod-c   10m   510Mi\n".to_string(),
+        ))
+        .unwrap();
+
+    let updated_store = settled_store(&store_rx);
+    let usage = &updated_store
+        .pods_widget
+        .as_ref()
+        .unwrap()
+        .state::<crate::widget_state::PodsState>()
+        .usage;
+    assert!(
+        usage["pod-a"].limit == Some(1536),
+        "store was: {:?}",
+        updated_store
+    );
+    assert!(
+        usage["pod-a"].cpu == vec![250, 300],
+        "store was: {:?}",
+        updated_store
+    );
+    assert!(
+        usage["pod-c"].limit.is_none() && usage["pod-c"].memory == vec![500, 510],
+        "store was: {:?}",
+        updated_store
+    );
+    // deleted since the last sample
+    assert!(
+        !usage.contains_key("pod-b"),
+        "store was: {:?}",
+        updated_store
+    );
+}
+
+#[test]
+fn test_split_view_follows_its_own_env() {
+    let (event_tx, action_rx, store_rx) = spawn_store(vec![
+        crate::widgets::create_split_logs_widget_data().get_event_handler(),
+        crate::widgets::create_split_pods_widget_data().get_event_handler(),
+    ]);
+
+    event_tx
+        .send(TUIEvent::EnvChange(crate::structs::KubeEnv::Dev))
+        .unwrap();
+    event_tx.send(TUIEvent::ToggleSplit).unwrap();
+    event_tx
+        .send(TUIEvent::AddSplitLog(
+            crate::structs::KubeEnv::Dev,
+            "from dev\n".to_string(),
+        ))
+        .unwrap();
+    event_tx
+        .send(TUIEvent::AddSplitLog(
+            crate::structs::KubeEnv::Prod,
+            "from prod\n".to_string(),
+        ))
+        .unwrap();
+
+    let mut updated_store = settled_store(&store_rx);
+    assert!(
+        updated_store.split_env == Some(crate::structs::KubeEnv::Prod),
+        "store was: {:?}",
+        updated_store
+    );
+    assert!(
+        updated_store
+            .split_logs_widget
+            .clone()
+            .unwrap()
+            .get_data()
+            .state
+            .lines()
+            == Some(vec!["from prod\n".to_string()]),
+        "store was: {:?}",
+        updated_store
+    );
+    assert!(
+        updated_store.split_logs_widget.as_ref().unwrap().widget.env
+            == Some(crate::structs::KubeEnv::Prod)
+            && updated_store.logs_widget.as_ref().unwrap().widget.env
+                == Some(crate::structs::KubeEnv::Dev)
+    );
+    let actions: Vec<TUIAction> = action_rx.try_iter().collect();
+    assert!(
+        actions
+            .iter()
+            .filter(|action| !matches!(action, TUIAction::SetNamespace(..)))
+            .take(2)
+            .eq([
+                &TUIAction::ChangeEnv(crate::structs::KubeEnv::Dev),
+                &TUIAction::OpenSplit(crate::structs::KubeEnv::Prod)
+            ]),
+        "actions were: {:?}",
+        actions
+    );
+
+    event_tx.send(TUIEvent::ToggleSplit).unwrap();
+    updated_store = settled_store(&store_rx);
+    assert!(updated_store.split_env.is_none());
+    assert!(updated_store
+        .split_pods_widget
+        .as_ref()
+        .unwrap()
+        .widget
+        .env
+        .is_none());
+    assert!(
+        updated_store
+            .split_logs_widget
+            .clone()
+            .unwrap()
+            .get_data()
+            .state
+            .lines()
+            .is_none(),
+        "store was: {:?}",
+        updated_store
+    );
+    assert!(action_rx.recv().unwrap() == TUIAction::CloseSplit);
+}
+
+#[test]
+fn test_namespace_picker_retargets_widgets() {
+    let (event_tx, action_rx, store_rx) = spawn_store(vec![
+        crate::widgets::create_namespaces_widget_data().get_event_handler(),
+    ]);
+
+    event_tx
+        .send(TUIEvent::EnvChange(crate::structs::KubeEnv::Dev))
+        .unwrap();
+    event_tx.send(TUIEvent::OpenNamespacePicker).unwrap();
+    event_tx
+        .send(TUIEvent::AddNamespaces(
+            "kube-system\nmyccv-dev-salespoint\nmonitoring\n".to_string(),
+        ))
+        .unwrap();
+    event_tx
+        .send(TUIEvent::SetNamespaceFilter("mon".to_string()))
+        .unwrap();
+
+    let mut updated_store = settled_store(&store_rx);
+    let namespaces_widget = updated_store.namespaces_widget.as_ref().unwrap();
+    assert!(updated_store.namespace_picker_open);
+    assert!(
+        namespaces_widget.get_data().state.lines()
+            == Some(vec![
+                "monitoring".to_string(),
+                "myccv-dev-salespoint".to_string()
+            ]),
+        "store was: {:?}",
+        updated_store
+    );
+
+    event_tx.send(TUIEvent::SelectNamespace).unwrap();
+    updated_store = settled_store(&store_rx);
+    assert!(!updated_store.namespace_picker_open);
+    assert!(
+        updated_store
+            .recent_namespaces
+            .current(crate::structs::KubeEnv::Dev)
+            == "monitoring",
+        "store was: {:?}",
+        updated_store
+    );
+    assert!(
+        updated_store
+            .header_widget
+            .clone()
+            .unwrap()
+            .state::<HeaderState>()
+            .namespace
+            == Some("monitoring".to_string()),
+        "store was: {:?}",
+        updated_store
+    );
+    let actions: Vec<TUIAction> = action_rx.try_iter().collect();
+    assert!(
+        actions.ends_with(&[
+            TUIAction::SetNamespace(crate::structs::KubeEnv::Dev, "monitoring".to_string()),
+            TUIAction::GetPods,
+            TUIAction::GetLogs,
+            TUIAction::GetEvents,
+            TUIAction::GetDeployments,
+        ]),
+        "actions were: {:?}",
+        actions
+    );
+}
+
+#[test]
+fn test_palette_filters_registered_commands() {
+    let (event_tx, action_rx, store_rx) = spawn_store_with(
+        vec![crate::widgets::create_palette_widget_data().get_event_handler()],
+        |store| {
+            store.commands = [
+                crate::app::global_commands(),
+                crate::widgets::create_deployments_widget_data().get_commands(),
+            ]
+            .concat();
+        },
+    );
+
+    event_tx.send(TUIEvent::OpenPalette).unwrap();
+    event_tx
+        .send(TUIEvent::SetPaletteFilter("rollout".to_string()))
+        .unwrap();
+
+    let mut updated_store = settled_store(&store_rx);
+    let palette_data = updated_store.palette_widget.as_ref().unwrap().get_data();
+    assert!(updated_store.palette_open);
+    assert!(
+        palette_data.state.lines().unwrap().len() == 1,
+        "store was: {:?}",
+        updated_store
+    );
+    assert!(crate::widgets::selected_row(palette_data) == Some("undo-rollout".to_string()));
+
+    event_tx.send(TUIEvent::ClosePalette).unwrap();
+    event_tx
+        .send(TUIEvent::RunAction(TUIAction::GetDeployments))
+        .unwrap();
+    event_tx.send(TUIEvent::Quit).unwrap();
+    updated_store = settled_store(&store_rx);
+    assert!(!updated_store.palette_open);
+    assert!(updated_store.quit);
+    assert!(action_rx
+        .try_iter()
+        .any(|action| matches!(action, TUIAction::GetDeployments)));
+}
+
+#[test]
+fn test_help_clears_the_unknown_key_hint() {
+    let (event_tx, _action_rx, store_rx) = spawn_store(vec![]);
+    let hint = |store: &Store| {
+        store
+            .header_widget
+            .as_ref()
+            .unwrap()
+            .state::<HeaderState>()
+            .hint
+            .clone()
+    };
+
+    event_tx
+        .send(TUIEvent::ShowHint("x does nothing here".to_string()))
+        .unwrap();
+    let mut updated_store = settled_store(&store_rx);
+    assert!(hint(&updated_store) == Some("x does nothing here".to_string()));
+    assert!(!updated_store.help_open);
+
+    event_tx.send(TUIEvent::ToggleHelp).unwrap();
+    updated_store = settled_store(&store_rx);
+    assert!(updated_store.help_open);
+    assert!(hint(&updated_store).is_none());
+
+    event_tx.send(TUIEvent::ToggleHelp).unwrap();
+    updated_store = settled_store(&store_rx);
+    assert!(!updated_store.help_open);
+}
+
+#[test]
+fn test_mouse_focus_scroll_and_row_selection() {
+    let (event_tx, _action_rx, store_rx) = spawn_store(vec![
+        crate::widgets::create_pods_widget_data().get_event_handler(),
+    ]);
+
+    event_tx
+        .send(TUIEvent::AddPods(
+            "NAME READY\npod-a 1/1\npod-b 1/1\n".to_string(),
+        ))
+        .unwrap();
+    event_tx
+        .send(TUIEvent::FocusWidget(CliWidgetId::GetEvents))
+        .unwrap();
+    event_tx
+        .send(TUIEvent::SelectRow(CliWidgetId::GetPods, 2))
+        .unwrap();
+    event_tx
+        .send(TUIEvent::ScrollWidget(CliWidgetId::GetLogs, -3, 10))
+        .unwrap();
+    let mut updated_store = settled_store(&store_rx);
+    let pods_widget = updated_store.pods_widget.clone().unwrap();
+    let logs_widget = updated_store.logs_widget.clone().unwrap();
+    assert!(!pods_widget.get_widget().is_selected);
+    assert!(
+        updated_store
+            .events_widget
+            .unwrap()
+            .get_widget()
+            .is_selected
+    );
+    assert!(selected_row(pods_widget.get_data()) == Some("pod-b".to_string()));
+    assert!(logs_widget.get_widget().scroll == Some(7));
+    assert!(logs_widget.get_widget().paused);
+
+    // scrolling back to the end follows the logs again
+    event_tx
+        .send(TUIEvent::ScrollWidget(CliWidgetId::GetLogs, 5, 10))
+        .unwrap();
+    updated_store = settled_store(&store_rx);
+    let logs_widget = updated_store.logs_widget.unwrap();
+    assert!(logs_widget.get_widget().scroll.is_none());
+    assert!(!logs_widget.get_widget().paused);
+}
+
+/// Streams lines out of a child at 10k lines/s and then as fast as possible into the logs widget,
+/// through `get_stream` like `kubectl logs` is followed, rendering every snapshot and printing
+/// how far behind they are. Run with
+/// `cargo test --release bench_log_throughput -- --ignored --nocapture`.
+#[test]
+#[ignore]
+fn bench_log_throughput() {
+    use std::{
+        io::Write,
+        process::{Command, Stdio},
+    };
+
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use crate::{action_handler::get_stream, ui::MainLayoutUI};
+
+    let (event_tx, _action_rx, store_rx) = spawn_store(vec![
+        crate::widgets::create_logs_widget_data().get_event_handler(),
+    ]);
+    settled_store(&store_rx);
+    let line = |index: usize| format!("2024-05-01T10:00:00Z INFO handled request {}\n", index);
+    // `cat` echoes `per_burst` lines every `every`, `get_stream` follows its stdout
+    let stream = move |bursts: usize, per_burst: usize, every: Duration| {
+        let mut child = Command::new("cat")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdin = child.stdin.take().unwrap();
+        thread::spawn(move || {
+            let start = Instant::now();
+            for burst in 0..bursts {
+                let lines: String = (burst * per_burst..(burst + 1) * per_burst)
+                    .map(line)
+                    .collect();
+                stdin.write_all(lines.as_bytes()).unwrap();
+                thread::sleep((every * (burst as u32 + 1)).saturating_sub(start.elapsed()));
+            }
+        });
+        let event_tx = event_tx.clone();
+        thread::spawn(move || {
+            get_stream(
+                Ok(child),
+                &event_tx,
+                "bench",
+                |_| false,
+                |line| Some(TUIEvent::AddLog(0, line)),
+            )
+        });
+    };
+    // renders the snapshots until one shows the last line, like the UI only the newest one
+    let mut terminal = Terminal::new(TestBackend::new(200, 50)).unwrap();
+    let mut catch_up = |last: usize| {
+        let mut snapshots = 0;
+        loop {
+            let mut store = store_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            snapshots += 1;
+            for newer in store_rx.try_iter() {
+                store = newer;
+                snapshots += 1;
+            }
+            let logs_widget = store.logs_widget.unwrap();
+            terminal
+                .draw(|f| logs_widget.render(f, &MainLayoutUI::new()))
+                .unwrap();
+            let lines = logs_widget.get_data().state.lines();
+            if lines.and_then(|lines| lines.last().cloned()) == Some(line(last)) {
+                return snapshots;
+            }
+        }
+    };
+
+    let (seconds, per_burst) = (3, 100);
+    stream(seconds * 100, per_burst, Duration::from_millis(10));
+    let start = Instant::now();
+    let snapshots = catch_up(seconds * 100 * per_burst - 1);
+    let lag = start
+        .elapsed()
+        .saturating_sub(Duration::from_secs(seconds as u64));
+    println!(
+        "10k lines/s for {}s: {} snapshots, {:?} behind after the last line",
+        seconds, snapshots, lag
+    );
+    assert!(
+        snapshots
+            <= seconds
+                * (Duration::from_secs(1).as_millis() / frame_interval().as_millis()) as usize
+                + 5
+    );
+    assert!(lag < frame_interval() * 5);
+
+    let lines = 100_000;
+    let start = Instant::now();
+    stream(1, lines, Duration::ZERO);
+    catch_up(lines - 1);
+    let per_second = lines as f64 / start.elapsed().as_secs_f64();
+    println!("as fast as possible: {:.0} lines/s", per_second);
+    assert!(per_second >= 10_000.0);
+}
+
+#[test]
+fn test_log_search_goes_back_through_the_matches() {
+    let (event_tx, _action_rx, store_rx) = spawn_store(vec![
+        crate::widgets::create_logs_widget_data().get_event_handler(),
+    ]);
+
+    event_tx
+        .send(TUIEvent::AddLog(
+            0,
+            "GET /a\nGET /b\nPOST /c\nGET /d\n".to_string(),
+        ))
+        .unwrap();
+    event_tx.send(TUIEvent::OpenLogSearch).unwrap();
+    event_tx
+        .send(TUIEvent::SetLogSearch("GET".to_string()))
+        .unwrap();
+    event_tx.send(TUIEvent::FindInLogs).unwrap();
+    event_tx.send(TUIEvent::FindInLogs).unwrap();
+    let updated_store = settled_store(&store_rx);
+    let logs_widget = updated_store.logs_widget.unwrap();
+    assert!(logs_widget.get_widget().scroll == Some(1));
+    assert!(logs_widget.get_widget().paused);
+
+    event_tx.send(TUIEvent::FindInLogs).unwrap();
+    event_tx.send(TUIEvent::FindInLogs).unwrap();
+    let updated_store = settled_store(&store_rx);
+    let logs_widget = updated_store.logs_widget.unwrap();
+    assert!(logs_widget.get_widget().scroll == Some(0));
+    assert!(logs_widget
+        .get_widget()
+        .title
+        .as_ref()
+        .unwrap()
+        .contains("no older match"));
+
+    event_tx.send(TUIEvent::CloseLogSearch).unwrap();
+    let updated_store = settled_store(&store_rx);
+    let logs_widget = updated_store.logs_widget.unwrap();
+    assert!(logs_widget.get_widget().title == Some("Salespoint Logs".to_string()));
+}
+
+#[test]
+fn test_in_flight_actions_can_be_cancelled() {
+    let (event_tx, action_rx, store_rx) = spawn_store(vec![
+        crate::widgets::create_pods_widget_data().get_event_handler(),
+    ]);
+
+    event_tx
+        .send(TUIEvent::ActionStarted(1, "pods".to_string()))
+        .unwrap();
+    event_tx
+        .send(TUIEvent::ActionStarted(2, "top".to_string()))
+        .unwrap();
+    event_tx.send(TUIEvent::SelectNextAction).unwrap();
+    let updated_store = settled_store(&store_rx);
+    let header = updated_store
+        .header_widget
+        .unwrap()
+        .state::<HeaderState>()
+        .clone();
+    assert!(header.selected_action == 1);
+    assert!(header.actions_text(Instant::now()) == Some("⟳ top 0s [2/2]".to_string()));
+
+    event_tx.send(TUIEvent::CancelSelectedAction).unwrap();
+    let action = action_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert!(
+        action == TUIAction::CancelAction(2),
+        "action was: {:?}",
+        action
+    );
+
+    event_tx.send(TUIEvent::ActionFinished(2)).unwrap();
+    let updated_store = settled_store(&store_rx);
+    let header = updated_store
+        .header_widget
+        .unwrap()
+        .state::<HeaderState>()
+        .clone();
+    assert!(header.selected_action == 0 && header.actions.len() == 1);
+}
+
+#[test]
+fn test_logs_are_exported_on_the_action_thread() {
+    let (event_tx, action_rx, _store_rx) = spawn_store(vec![
+        crate::widgets::create_logs_widget_data().get_event_handler(),
+    ]);
+
+    event_tx
+        .send(TUIEvent::AddLog(0, "first\n".to_string()))
+        .unwrap();
+    event_tx.send(TUIEvent::ExportLogs).unwrap();
+    event_tx
+        .send(TUIEvent::AddLog(0, "after the export\n".to_string()))
+        .unwrap();
+    let Ok(TUIAction::ExportLogs(export)) = action_rx.recv_timeout(Duration::from_secs(1)) else {
+        panic!("the export was not sent to the action thread");
+    };
+    let mut exported = vec![];
+    assert!(export.write(&mut exported).unwrap() == 1);
+    assert!(exported == b"first\n");
 }
@@ -0,0 +1,1000 @@
+use std::{
+    any::Any,
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    io::{self, Write},
+    mem,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use log::warn;
+
+use crate::{
+    errors::{history_size, ErrorReport},
+    log_history::LogHistory,
+    log_merge::TimeColumn,
+    log_sources::LogSource,
+    structs::{Direction2, KubeEnv},
+    timestamp::human_age,
+    truncator::{LinePosition, TopTruncator, Truncatorix},
+};
+
+/// What a widget shows, owned by the widget. The store and the renderers only go through this
+/// trait, the handlers of a widget get their own type back with `CliWidgetData::state`.
+pub trait WidgetState: Debug + Send + Sync {
+    /// Lines rendered in the body of the widget, `None` hides the widget.
+    fn lines(&self) -> Option<Vec<String>>;
+
+    /// Forgets everything shown, like after pointing the widget at another namespace.
+    fn clear(&mut self);
+
+    /// The selectable rows, for widgets listing them.
+    fn table(&self) -> Option<&TableState> {
+        None
+    }
+
+    fn table_mut(&mut self) -> Option<&mut TableState> {
+        None
+    }
+
+    /// The log shown, for widgets following one.
+    fn log_mut(&mut self) -> Option<&mut LogState> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn clone_box(&self) -> Box<dyn WidgetState>;
+}
+
+impl Clone for Box<dyn WidgetState> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+macro_rules! widget_state_any {
+    () => {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn clone_box(&self) -> Box<dyn WidgetState> {
+            Box::new(self.clone())
+        }
+    };
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum AuthStatus {
+    #[default]
+    LoggedOut,
+    /// Logged in to AWS, the cluster was not reached yet.
+    LoggedIn,
+    Connected,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct HeaderState {
+    pub env: Option<KubeEnv>,
+    pub namespace: Option<String>,
+    /// Environment of the right hand pane, when the view is split.
+    pub split_env: Option<KubeEnv>,
+    pub auth: AuthStatus,
+    /// Summary of the latest error, until it is cleared.
+    pub error: Option<String>,
+    /// Errors reported, the oldest first.
+    pub errors: Vec<ErrorReport>,
+    /// Errors reported since the error history was last open.
+    pub unread_errors: usize,
+    /// Lines the error history is scrolled down by.
+    pub errors_scroll: u16,
+    /// Short tip shown instead of the error until the next bound key.
+    pub hint: Option<String>,
+    /// Actions in flight, the oldest first.
+    pub actions: Vec<InFlightAction>,
+    /// Index in `actions` of the one a cancel is for.
+    pub selected_action: usize,
+}
+
+/// An action running on a thread of its own, as shown in the header.
+#[derive(Clone, Debug)]
+pub struct InFlightAction {
+    pub id: u64,
+    pub label: String,
+    pub started: Instant,
+}
+
+impl HeaderState {
+    /// Shows the error and keeps it in the history, counting it when it repeats the last one.
+    pub fn report(&mut self, report: ErrorReport) {
+        self.error = Some(report.summary());
+        if let Some(last) = self
+            .errors
+            .last_mut()
+            .filter(|last| last.repeated_by(&report))
+        {
+            last.count += 1;
+            last.time = report.time;
+            return;
+        }
+        self.errors.push(report);
+        let overflow = self.errors.len().saturating_sub(history_size());
+        self.errors.drain(..overflow);
+        self.unread_errors += 1;
+    }
+
+    /// The selected action in flight, with how long it runs, and how many there are.
+    pub fn actions_text(&self, now: Instant) -> Option<String> {
+        let action = self.actions.get(self.selected_action)?;
+        let elapsed = human_age(now.duration_since(action.started).as_secs() as i64);
+        Some(match self.actions.len() {
+            1 => format!("⟳ {} {}", action.label, elapsed),
+            count => format!(
+                "⟳ {} {} [{}/{}]",
+                action.label,
+                elapsed,
+                self.selected_action + 1,
+                count
+            ),
+        })
+    }
+}
+
+impl WidgetState for HeaderState {
+    fn lines(&self) -> Option<Vec<String>> {
+        None
+    }
+
+    fn clear(&mut self) {
+        *self = HeaderState::default();
+    }
+
+    widget_state_any!();
+}
+
+/// Lines kept by a log widget when nothing else limits them.
+pub const LOG_CAPACITY: usize = 10_000;
+
+/// Lines per shared chunk of a log.
+const CHUNK_LINES: usize = 256;
+
+/// Most lines paged in from the history, as a view scrolls no further than this anyway.
+const PAGED_LINES: usize = u16::MAX as usize;
+
+#[derive(Clone, Debug)]
+struct LogLine {
+    text: String,
+    received: Instant,
+}
+
+/// The last lines of a stream, its truncator dropping the oldest ones as new ones come in. With
+/// a history the dropped lines go to disk, from where they are paged back in when scrolled to.
+///
+/// Full chunks of lines are never changed again and shared between the copies of the state, so
+/// a snapshot of the store only copies the lines of the chunk still being filled.
+#[derive(Clone, Debug)]
+pub struct LogState {
+    /// Old lines the truncator lets outlive the others, like errors, before all other lines.
+    kept: Arc<Vec<LogLine>>,
+    chunks: VecDeque<Arc<Vec<LogLine>>>,
+    /// Lines of the first chunk dropped already.
+    dropped: usize,
+    tail: Vec<LogLine>,
+    /// Length of the text of all lines.
+    bytes: usize,
+    /// Whether anything was received since the last `clear`, even an empty chunk.
+    started: bool,
+    truncator: Arc<dyn Truncatorix>,
+    history: Option<Arc<Mutex<LogHistory>>>,
+    /// The last lines of the history, shown before the lines in memory.
+    paged: Vec<String>,
+    pub search: Option<LogSearch>,
+}
+
+/// A log as it was when its export was asked for.
+#[derive(Clone)]
+pub struct LogExport {
+    log: LogState,
+    /// Lines of the history then, the ones spilled to it since are still in `log`.
+    history_lines: usize,
+}
+
+impl LogExport {
+    /// Writes the history and the lines in memory to `out`, returns the number of lines.
+    pub fn write(&self, out: &mut impl Write) -> io::Result<usize> {
+        if let Some(history) = &self.log.history {
+            history.lock().unwrap().export(out, self.history_lines)?;
+        }
+        for line in self.log.iter() {
+            out.write_all(line.text.as_bytes())?;
+        }
+        Ok(self.history_lines + self.log.len())
+    }
+}
+
+impl Debug for LogExport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "LogExport({} lines)",
+            self.history_lines + self.log.len()
+        )
+    }
+}
+
+impl PartialEq for LogExport {
+    fn eq(&self, other: &Self) -> bool {
+        self.history_lines == other.history_lines
+            && self
+                .log
+                .iter()
+                .map(|line| &line.text)
+                .eq(other.log.iter().map(|line| &line.text))
+    }
+}
+
+/// A search through a log, typed while its view stays where it is.
+#[derive(Clone, Debug, Default)]
+pub struct LogSearch {
+    pub query: String,
+    /// Line of the last match, counted from the oldest line of the history.
+    pub matched: Option<usize>,
+    /// Whether the last search found nothing older.
+    pub exhausted: bool,
+}
+
+impl Default for LogState {
+    fn default() -> Self {
+        LogState::new(Arc::new(TopTruncator::new(LOG_CAPACITY)))
+    }
+}
+
+impl LogState {
+    pub fn new(truncator: Arc<dyn Truncatorix>) -> Self {
+        LogState {
+            kept: Arc::new(vec![]),
+            chunks: VecDeque::new(),
+            dropped: 0,
+            tail: vec![],
+            bytes: 0,
+            started: false,
+            truncator,
+            history: None,
+            paged: vec![],
+            search: None,
+        }
+    }
+
+    /// Keeps the lines dropped from memory in `history` instead of forgetting them.
+    pub fn spill_to(&mut self, history: LogHistory) {
+        self.history = Some(Arc::new(Mutex::new(history)));
+    }
+
+    /// Appends a chunk of the stream, continuing its last line when that one was not ended yet.
+    pub fn push(&mut self, text: &str) {
+        self.push_at(text, Instant::now())
+    }
+
+    fn push_at(&mut self, text: &str, now: Instant) {
+        self.started = true;
+        for part in text.split_inclusive('\n') {
+            self.bytes += part.len();
+            match self.tail.last_mut() {
+                Some(last) if !last.text.ends_with('\n') => last.text.push_str(part),
+                _ => self.tail.push(LogLine {
+                    text: part.to_string(),
+                    received: now,
+                }),
+            }
+        }
+        if self.tail.len() > CHUNK_LINES {
+            // the last line may still be continued, it stays behind
+            let open = self.tail.pop();
+            self.chunks.push_back(Arc::new(mem::take(&mut self.tail)));
+            self.tail.extend(open);
+        }
+        self.truncate(now);
+    }
+
+    pub fn len(&self) -> usize {
+        self.kept.len() + self.recent_len()
+    }
+
+    /// Lines after the kept ones.
+    fn recent_len(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.len()).sum::<usize>() - self.dropped + self.tail.len()
+    }
+
+    fn history_len(&self) -> usize {
+        self.history
+            .as_ref()
+            .map_or(0, |history| history.lock().unwrap().len())
+    }
+
+    /// Pages in up to `count` lines of the history before the ones shown, returns how many.
+    pub fn page_in(&mut self, count: usize) -> usize {
+        let Some(history) = &self.history else {
+            return 0;
+        };
+        let mut history = history.lock().unwrap();
+        let end = history.len() - self.paged.len();
+        match history.read(end.saturating_sub(count)..end) {
+            Ok(lines) => {
+                let paged = lines.len();
+                self.paged.splice(0..0, lines);
+                paged
+            }
+            Err(error) => {
+                warn!("paging in the log history failed: {}", error);
+                0
+            }
+        }
+    }
+
+    /// Stops showing the lines paged in from the history.
+    pub fn page_out(&mut self) {
+        self.paged = vec![];
+    }
+
+    /// Line of the last match of `query` before the line `before`, both counted from the oldest
+    /// line of the history.
+    pub fn find_before(&self, query: &str, before: usize) -> Option<usize> {
+        let history_len = self.history_len();
+        let in_memory = self
+            .iter()
+            .take(before.saturating_sub(history_len))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rposition(|line| line.text.contains(query));
+        if let Some(found) = in_memory {
+            return Some(history_len + found);
+        }
+        let history = self.history.as_ref()?;
+        history
+            .lock()
+            .unwrap()
+            .rfind(query, before.min(history_len))
+            .inspect_err(|error| warn!("searching the log history failed: {}", error))
+            .ok()
+            .flatten()
+    }
+
+    /// Line after the last one, counted from the oldest line of the history.
+    pub fn end(&self) -> usize {
+        self.history_len() + self.len()
+    }
+
+    /// Pages in the history up to `line`, returns where it is shown, or the first line shown when
+    /// it could not be paged in.
+    pub fn show(&mut self, line: usize) -> usize {
+        let first_shown = self.history_len() - self.paged.len();
+        if line < first_shown {
+            let paged = self.page_in(first_shown - line);
+            return line.saturating_sub(first_shown - paged);
+        }
+        line - first_shown
+    }
+
+    /// The log as it is now, to be written out away from the store thread.
+    pub fn export(&self) -> LogExport {
+        LogExport {
+            log: self.clone(),
+            history_lines: self.history_len(),
+        }
+    }
+
+    fn spill(&mut self, lines: Vec<String>) {
+        let Some(history) = &self.history else {
+            return;
+        };
+        if let Err(error) = history
+            .lock()
+            .unwrap()
+            .append(lines.iter().map(|line| line.as_str()))
+        {
+            warn!("writing the log history failed: {}", error);
+        }
+        if !self.paged.is_empty() {
+            // the paged lines stay the end of the history, so the view does not jump, until the
+            // oldest ones have to go
+            self.paged.extend(lines);
+            let excess = self.paged.len().saturating_sub(PAGED_LINES);
+            self.paged.drain(..excess);
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &LogLine> {
+        self.kept.iter().chain(self.recent())
+    }
+
+    fn recent(&self) -> impl Iterator<Item = &LogLine> {
+        let dropped = self.dropped;
+        self.chunks
+            .iter()
+            .enumerate()
+            .flat_map(move |(index, chunk)| chunk[if index == 0 { dropped } else { 0 }..].iter())
+            .chain(self.tail.iter())
+    }
+
+    /// Drops the oldest lines the truncator rejects, keeping the ones it lets outlive them.
+    fn truncate(&mut self, now: Instant) {
+        let (mut lines, mut bytes) = (self.len(), self.bytes);
+        let position = |line: &LogLine, lines, bytes| LinePosition {
+            lines,
+            bytes,
+            age: now.saturating_duration_since(line.received),
+        };
+        let mut spilled = vec![];
+        let mut still_kept = Vec::with_capacity(self.kept.len());
+        for line in self.kept.iter() {
+            let drops = self
+                .truncator
+                .drops(&line.text, position(line, lines, bytes));
+            if drops && self.history.is_some() {
+                spilled.push(line.text.clone());
+            }
+            still_kept.push(!drops);
+            lines -= 1;
+            bytes -= line.text.len();
+        }
+        let mut examined = 0;
+        let mut kept = vec![];
+        for line in self.recent() {
+            if !self
+                .truncator
+                .drops(&line.text, position(line, lines, bytes))
+            {
+                if !self.truncator.outlives(&line.text) {
+                    break;
+                }
+                kept.push(line.clone());
+            } else if self.history.is_some() {
+                spilled.push(line.text.clone());
+            }
+            examined += 1;
+            lines -= 1;
+            bytes -= line.text.len();
+        }
+        let all_kept = still_kept.iter().all(|&still| still);
+        if all_kept && kept.len() == examined {
+            return;
+        }
+        if !all_kept {
+            // only when the truncator gives up on kept lines, which are few
+            let (still, gone): (Vec<_>, Vec<_>) = self
+                .kept
+                .iter()
+                .zip(still_kept)
+                .partition(|(_, still)| *still);
+            self.bytes -= gone.iter().map(|(line, _)| line.text.len()).sum::<usize>();
+            self.kept = Arc::new(still.into_iter().map(|(line, _)| line.clone()).collect());
+        }
+        self.drop_front(examined);
+        self.spill(spilled);
+        if !kept.is_empty() {
+            self.bytes += kept.iter().map(|line| line.text.len()).sum::<usize>();
+            Arc::make_mut(&mut self.kept).extend(kept);
+        }
+    }
+
+    fn drop_front(&mut self, count: usize) {
+        let text_len = |lines: &[LogLine]| lines.iter().map(|line| line.text.len()).sum::<usize>();
+        let mut count = count;
+        while count > 0 {
+            match self.chunks.front() {
+                Some(chunk) if chunk.len() - self.dropped <= count => {
+                    count -= chunk.len() - self.dropped;
+                    self.bytes -= text_len(&chunk[self.dropped..]);
+                    self.chunks.pop_front();
+                    self.dropped = 0;
+                }
+                Some(chunk) => {
+                    self.bytes -= text_len(&chunk[self.dropped..self.dropped + count]);
+                    self.dropped += count;
+                    count = 0;
+                }
+                None => {
+                    self.bytes -= text_len(&self.tail[..count]);
+                    self.tail.drain(..count);
+                    count = 0;
+                }
+            }
+        }
+    }
+}
+
+impl WidgetState for LogState {
+    fn lines(&self) -> Option<Vec<String>> {
+        if !self.started {
+            return None;
+        }
+        let mut lines = Vec::with_capacity(self.paged.len() + self.len());
+        lines.extend(self.paged.iter().cloned());
+        lines.extend(self.iter().map(|line| line.text.clone()));
+        Some(lines)
+    }
+
+    fn log_mut(&mut self) -> Option<&mut LogState> {
+        Some(self)
+    }
+
+    fn clear(&mut self) {
+        let history = self.history.take();
+        if let Some(history) = &history {
+            history.lock().unwrap().clear();
+        }
+        *self = LogState::new(self.truncator.clone());
+        self.history = history;
+    }
+
+    widget_state_any!();
+}
+
+/// The logs of every source in a tab of their own, led by a tab of all of them together when
+/// there are several.
+#[derive(Clone, Debug)]
+pub struct LogTabsState {
+    pub sources: Vec<LogSource>,
+    /// Lines of each source.
+    logs: Vec<LogState>,
+    /// Lines of all sources, prefixed with the name of theirs.
+    all: LogState,
+    /// Tab shown, the first one being the "all" tab when there are several sources.
+    pub tab: usize,
+    /// How the timestamps of `--timestamps` are shown.
+    pub time: TimeColumn,
+    /// What goes wrong following each source, like reconnecting.
+    pub statuses: Vec<Option<String>>,
+}
+
+impl LogTabsState {
+    pub fn new(sources: Vec<LogSource>, new_log: impl Fn() -> LogState) -> Self {
+        LogTabsState {
+            logs: sources.iter().map(|_| new_log()).collect(),
+            statuses: vec![None; sources.len()],
+            all: new_log(),
+            sources,
+            tab: 0,
+            time: TimeColumn::from_config(),
+        }
+    }
+
+    fn has_all_tab(&self) -> bool {
+        self.sources.len() > 1
+    }
+
+    pub fn tab_names(&self) -> Vec<&str> {
+        let names = self.sources.iter().map(|source| source.name.as_str());
+        match self.has_all_tab() {
+            true => ["all"].into_iter().chain(names).collect(),
+            false => names.collect(),
+        }
+    }
+
+    /// Whether the tab shown has the lines of all sources.
+    pub fn all_shown(&self) -> bool {
+        self.has_all_tab() && self.tab == 0
+    }
+
+    /// Appends to the log of `source` the lines its filter keeps.
+    pub fn push(&mut self, source: usize, text: &str) {
+        let Some(log_source) = self.sources.get(source) else {
+            return;
+        };
+        let kept: String = text
+            .split_inclusive('\n')
+            .filter(|line| log_source.keeps(line))
+            .collect();
+        if kept.is_empty() && !text.is_empty() {
+            return;
+        }
+        self.logs[source].push(&kept);
+        if self.has_all_tab() {
+            let prefixed: String = kept
+                .split_inclusive('\n')
+                .map(|line| format!("[{}] {}", log_source.name, line))
+                .collect();
+            self.all.push(&prefixed);
+        }
+    }
+
+    pub fn current(&self) -> &LogState {
+        match (self.all_shown(), self.has_all_tab()) {
+            (true, _) => &self.all,
+            (false, true) => &self.logs[self.tab - 1],
+            (false, false) => &self.logs[self.tab],
+        }
+    }
+
+    pub fn current_mut(&mut self) -> &mut LogState {
+        match (self.all_shown(), self.has_all_tab()) {
+            (true, _) => &mut self.all,
+            (false, true) => &mut self.logs[self.tab - 1],
+            (false, false) => &mut self.logs[self.tab],
+        }
+    }
+
+    /// Shows the tab `offset` tabs away, wrapping around.
+    pub fn move_tab(&mut self, offset: isize) {
+        let tabs = self.tab_names().len() as isize;
+        self.tab = (self.tab as isize + offset).rem_euclid(tabs) as usize;
+    }
+}
+
+impl WidgetState for LogTabsState {
+    fn lines(&self) -> Option<Vec<String>> {
+        self.current().lines()
+    }
+
+    fn clear(&mut self) {
+        self.logs.iter_mut().for_each(|log| log.clear());
+        self.all.clear();
+    }
+
+    fn log_mut(&mut self) -> Option<&mut LogState> {
+        Some(self.current_mut())
+    }
+
+    widget_state_any!();
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct EventsState {
+    pub log: LogState,
+    /// Only the events of this pod are shown.
+    pub pod: Option<String>,
+}
+
+impl WidgetState for EventsState {
+    fn lines(&self) -> Option<Vec<String>> {
+        self.log.lines()
+    }
+
+    fn clear(&mut self) {
+        self.log.clear();
+    }
+
+    fn log_mut(&mut self) -> Option<&mut LogState> {
+        Some(&mut self.log)
+    }
+
+    widget_state_any!();
+}
+
+/// Rows of a `kubectl get` like table, selected by the name in their first column.
+#[derive(Clone, Debug, Default)]
+pub struct TableState {
+    rows: Option<Vec<String>>,
+    selected: Option<String>,
+}
+
+impl TableState {
+    /// Replaces the rows, keeping the selection when the row is still listed.
+    pub fn set_rows(&mut self, rows: &str) {
+        self.rows = Some(rows.lines().map(|line| line.to_string()).collect());
+        let names = self.row_names();
+        if !self
+            .selected
+            .as_ref()
+            .is_some_and(|selected| names.contains(selected))
+        {
+            self.selected = names.first().cloned();
+        }
+    }
+
+    /// Names of the listed rows, skipping the `NAME ...` header.
+    pub fn row_names(&self) -> Vec<String> {
+        self.rows
+            .iter()
+            .flatten()
+            .filter(|line| !line.starts_with("NAME "))
+            .filter_map(|line| line.split_whitespace().next())
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        self.selected.as_deref()
+    }
+
+    pub fn move_selection(&mut self, direction: &Direction2) {
+        let row_names = self.row_names();
+        let index = self
+            .selected
+            .as_ref()
+            .and_then(|selected| row_names.iter().position(|row| row == selected));
+        let new_index = match (direction, index) {
+            (Direction2::Down, Some(index)) => Some((index + 1).min(row_names.len() - 1)),
+            (Direction2::Up, Some(index)) => Some(index.saturating_sub(1)),
+            (Direction2::Down | Direction2::Up, None) if !row_names.is_empty() => Some(0),
+            _ => None,
+        };
+        if let Some(new_index) = new_index {
+            self.selected = Some(row_names[new_index].clone());
+        }
+    }
+
+    /// The scroll offset that shows the selected row in `height` lines, scrolling no further than
+    /// needed.
+    pub fn scroll_to_selection(&self, height: u16) -> u16 {
+        let line = self.rows.iter().flatten().position(|row| {
+            !row.starts_with("NAME ") && row.split_whitespace().next() == self.selected()
+        });
+        (line.unwrap_or(0) + 1).saturating_sub(height as usize) as u16
+    }
+
+    /// Selects the row shown on `line`, the header line selects nothing.
+    pub fn select_line(&mut self, line: usize) {
+        let row = self
+            .rows
+            .iter()
+            .flatten()
+            .nth(line)
+            .filter(|row| !row.starts_with("NAME "))
+            .and_then(|row| row.split_whitespace().next());
+        if let Some(row) = row {
+            self.selected = Some(row.to_string());
+        }
+    }
+}
+
+impl WidgetState for TableState {
+    fn lines(&self) -> Option<Vec<String>> {
+        self.rows.clone()
+    }
+
+    fn clear(&mut self) {
+        self.rows = None;
+        self.selected = None;
+    }
+
+    fn table(&self) -> Option<&TableState> {
+        Some(self)
+    }
+
+    fn table_mut(&mut self) -> Option<&mut TableState> {
+        Some(self)
+    }
+
+    widget_state_any!();
+}
+
+/// Samples of `kubectl top` for a pod, the oldest first, in millicores and mebibytes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PodUsage {
+    pub cpu: Vec<u64>,
+    pub memory: Vec<u64>,
+    /// Sum of the memory limits of its containers.
+    pub limit: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PodsState {
+    pub table: TableState,
+    pub usage: HashMap<String, PodUsage>,
+}
+
+impl WidgetState for PodsState {
+    fn lines(&self) -> Option<Vec<String>> {
+        self.table.lines()
+    }
+
+    fn clear(&mut self) {
+        self.table.clear();
+        self.usage.clear();
+    }
+
+    fn table(&self) -> Option<&TableState> {
+        Some(&self.table)
+    }
+
+    fn table_mut(&mut self) -> Option<&mut TableState> {
+        Some(&mut self.table)
+    }
+
+    widget_state_any!();
+}
+
+/// A full screen list to pick from while typing a filter.
+#[derive(Clone, Debug, Default)]
+pub struct PickerState {
+    pub table: TableState,
+    pub filter: String,
+    /// Everything to pick from, before filtering.
+    pub options: Vec<String>,
+}
+
+impl WidgetState for PickerState {
+    fn lines(&self) -> Option<Vec<String>> {
+        self.table.lines()
+    }
+
+    fn clear(&mut self) {
+        self.table.clear();
+    }
+
+    fn table(&self) -> Option<&TableState> {
+        Some(&self.table)
+    }
+
+    fn table_mut(&mut self) -> Option<&mut TableState> {
+        Some(&mut self.table)
+    }
+
+    widget_state_any!();
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DescribeState {
+    /// Output of `kubectl describe`, one entry per line.
+    pub description: Vec<String>,
+    /// The description with its collapsed sections folded, `None` while closed.
+    pub visible: Option<Vec<String>>,
+    /// Section the cursor is on.
+    pub section: Option<String>,
+    pub collapsed: Vec<String>,
+}
+
+impl WidgetState for DescribeState {
+    fn lines(&self) -> Option<Vec<String>> {
+        self.visible.clone()
+    }
+
+    fn clear(&mut self) {
+        *self = DescribeState::default();
+    }
+
+    widget_state_any!();
+}
+
+#[test]
+fn test_log_state_joins_chunks_and_drops_the_oldest_lines() {
+    let mut log = LogState::new(Arc::new(TopTruncator::new(3)));
+    assert!(log.lines().is_none());
+    log.push("first\nsec");
+    log.push("ond\nthird\n");
+    assert!(log.lines() == Some(vec!["first\n".into(), "second\n".into(), "third\n".into()]));
+    log.push("fourth");
+    assert!(log.lines() == Some(vec!["second\n".into(), "third\n".into(), "fourth".into()]));
+    assert!(log.bytes == "second\nthird\nfourth".len());
+
+    let mut log = LogState::new(Arc::new(TopTruncator::new(1000)));
+    for line in 0..2000 {
+        log.push(&format!("{}\n", line));
+    }
+    let lines = log.lines().unwrap();
+    assert!(lines.len() == 1000 && lines[0] == "1000\n" && lines[999] == "1999\n");
+    assert!(log
+        .clone()
+        .chunks
+        .iter()
+        .all(|chunk| Arc::strong_count(chunk) == 2));
+}
+
+#[test]
+fn test_log_state_keeps_errors_longer() {
+    use crate::truncator::{AgeTruncator, AnyTruncator, BytesTruncator, ErrorKeepingTruncator};
+    use std::time::Duration;
+
+    let truncator = ErrorKeepingTruncator::new(
+        Box::new(AnyTruncator::new(vec![
+            Box::new(BytesTruncator::new(1000)),
+            Box::new(AgeTruncator::new(Duration::from_secs(10))),
+        ])),
+        3,
+    );
+    let mut log = LogState::new(Arc::new(truncator));
+    let start = Instant::now();
+    log.push_at("info 1\nERROR 2\ninfo 3\n", start);
+    log.push_at("info 4\n", start + Duration::from_secs(15));
+    assert!(log.lines() == Some(vec!["ERROR 2\n".into(), "info 4\n".into()]));
+    assert!(log.bytes == "ERROR 2\ninfo 4\n".len());
+    for second in 16..300 {
+        log.push_at(
+            &format!("info {}\n", second),
+            start + Duration::from_secs(second),
+        );
+    }
+    let lines = log.lines().unwrap();
+    assert!(lines.len() == 11 && lines[0] == "info 289\n");
+    assert!(log.bytes == lines.concat().len());
+
+    log.push_at("ERROR 300\n", start + Duration::from_secs(300));
+    log.push_at("info 315\n", start + Duration::from_secs(315));
+    assert!(log.kept.len() == 1 && log.lines().unwrap()[0] == "ERROR 300\n");
+    let snapshot = log.clone();
+    log.push_at("info 316\n", start + Duration::from_secs(316));
+    // the kept errors are not copied for lines that are not kept
+    assert!(Arc::ptr_eq(&log.kept, &snapshot.kept));
+    log.push_at("info 335\n", start + Duration::from_secs(335));
+    assert!(log.kept.is_empty() && log.lines() == Some(vec!["info 335\n".into()]));
+    assert!(log.bytes == "info 335\n".len());
+}
+
+#[test]
+fn test_log_state_spills_to_its_history() {
+    let dir = std::env::temp_dir().join(format!("aws_cli-spill-test-{}", std::process::id()));
+    let mut log = LogState::new(Arc::new(TopTruncator::new(3)));
+    log.spill_to(LogHistory::create(dir.clone()).unwrap());
+    for line in 0..10 {
+        log.push(&format!("line {}\n", line));
+    }
+    assert!(log.lines().unwrap().len() == 3 && log.end() == 10);
+    assert!(log.find_before("line 8", log.end()) == Some(8));
+    assert!(log.find_before("line 2", log.end()) == Some(2));
+    assert!(log.find_before("line 2", 2).is_none());
+
+    assert!(log.page_in(2) == 2);
+    assert!(log.lines().unwrap()[0] == "line 5\n");
+    log.push("line 10\n");
+    // the paged lines keep growing instead of the view moving
+    assert!(log.lines().unwrap().len() == 6);
+    assert!(log.show(1) == 0);
+    assert!(log.lines().unwrap()[0] == "line 1\n");
+
+    let mut exported = vec![];
+    assert!(log.export().write(&mut exported).unwrap() == 11);
+    let expected: String = (0..11).map(|line| format!("line {}\n", line)).collect();
+    assert!(String::from_utf8(exported).unwrap() == expected);
+
+    // the history can no longer be read, the match is shown at the top of what is there
+    log.page_out();
+    let first_shown = log.end() - log.len();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(log.show(2) == 0 && log.end() - log.lines().unwrap().len() == first_shown);
+
+    log.clear();
+    assert!(log.end() == 0);
+}
+
+#[test]
+fn test_log_tabs_state_filters_and_prefixes_sources() {
+    let source = |name: &str, filter: Option<&str>| LogSource {
+        name: name.to_string(),
+        selector: format!("app={}", name),
+        container: None,
+        namespace: None,
+        env: None,
+        filter: filter.map(|filter| filter.to_string()),
+    };
+    let mut tabs = LogTabsState::new(
+        vec![source("api", None), source("web", Some("ERROR"))],
+        LogState::default,
+    );
+    assert!(tabs.tab_names() == vec!["all", "api", "web"]);
+    tabs.push(0, "GET /\n");
+    tabs.push(1, "INFO fine\nERROR boom\n");
+    assert!(tabs.lines() == Some(vec!["[api] GET /\n".into(), "[web] ERROR boom\n".into()]));
+    tabs.move_tab(-1);
+    assert!(tabs.lines() == Some(vec!["ERROR boom\n".into()]));
+    tabs.move_tab(2);
+    assert!(tabs.tab == 1 && tabs.lines() == Some(vec!["GET /\n".into()]));
+    tabs.clear();
+    assert!(tabs.lines().is_none());
+}
+
+#[test]
+fn test_table_state_keeps_the_selection_while_listed() {
+    let mut table = TableState::default();
+    table.set_rows("NAME   READY\npod-a  1/1\npod-b  1/1\n");
+    assert!(table.selected() == Some("pod-a"));
+    table.move_selection(&Direction2::Down);
+    table.set_rows("NAME   READY\npod-b  1/1\npod-c  1/1\n");
+    assert!(table.selected() == Some("pod-b"));
+    table.set_rows("NAME   READY\npod-c  1/1\n");
+    assert!(table.selected() == Some("pod-c"));
+    table.select_line(0);
+    assert!(table.selected() == Some("pod-c"));
+
+    table.set_rows("NAME   READY\npod-a  1/1\npod-b  1/1\npod-c  1/1\npod-d  1/1\n");
+    assert!(table.scroll_to_selection(2) == 2);
+    assert!(table.scroll_to_selection(4) == 0);
+    table.select_line(1);
+    assert!(table.scroll_to_selection(2) == 0);
+}
@@ -1,20 +1,74 @@
-use std::{collections::HashMap, sync::mpsc::Sender};
+use std::{
+    collections::HashSet,
+    sync::mpsc::Sender,
+    time::{Duration, Instant},
+};
 
 use crossterm::event::KeyCode;
 use log::trace;
 use ratatui::{
-    layout::{Alignment, Rect},
-    style::{Color, Style},
-    text::Span,
-    widgets::{Block, Borders, Paragraph, Wrap},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Sparkline, Wrap},
     Frame,
 };
 
 use crate::{
-    structs::{CliWidgetData, Store, TUIAction, TUIEvent},
+    config::config,
+    fuzzy::fuzzy_filter,
+    log_history::widget_history,
+    log_sources::log_sources,
+    pod_watch::POD_MARKER,
+    structs::{CliWidgetData, Direction2, KubeEnv, Store, TUIAction, TUIEvent},
+    theme::theme,
+    timestamp::{human_age, now_secs, parse_rfc3339},
+    truncator::widget_truncator,
     ui::MainLayoutUI,
+    widget_state::{
+        AuthStatus, DescribeState, EventsState, HeaderState, LogSearch, LogState, LogTabsState,
+        PickerState, PodUsage, PodsState, TableState, WidgetState,
+    },
 };
 
+/// Turns a single line of widget data into a styled line, or `None` to hide it.
+pub type LineFormatter = fn(&CliWidgetData, &str) -> Option<Line<'static>>;
+
+/// Extra content, like charts, drawn in columns of its own at the right of the rows of a widget.
+#[derive(Clone, Copy, Debug)]
+pub struct Decorator {
+    /// Widths of the columns for an inner widget width, the rows keep what is left.
+    pub widths: fn(u16) -> Vec<u16>,
+    /// Draws in the columns, each as high as the inner widget area.
+    pub draw: fn(&CliWidgetData, &mut Frame, &[Rect]),
+}
+
+/// Handles a key press for a widget, returning `None` when the key was consumed.
+pub type Keymap = fn(KeyCode, &Store, &Sender<TUIEvent>) -> Option<()>;
+
+/// An entry of the command palette, registered by the widget it belongs to. Its `id` is the
+/// action name keys are bound to.
+#[derive(Clone, Copy, Debug)]
+pub struct Command {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub run: fn(&Store, &Sender<TUIEvent>),
+}
+
+impl Command {
+    pub const fn new(
+        id: &'static str,
+        description: &'static str,
+        run: fn(&Store, &Sender<TUIEvent>),
+    ) -> Self {
+        Command {
+            id,
+            description,
+            run,
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Hash, Eq, PartialEq)]
 pub enum CliWidgetId {
     #[default]
@@ -24,7 +78,13 @@ pub enum CliWidgetId {
     GetLoginLogs,
     GetPods,
     Tail,
-    LoginRequest,
+    GetEvents,
+    DescribePod,
+    GetDeployments,
+    SplitLogs,
+    SplitPods,
+    Namespaces,
+    Palette,
 }
 
 pub trait RenderWidget {
@@ -32,39 +92,57 @@ pub trait RenderWidget {
     fn get_widget(&self) -> &CliWidget;
     fn get_widget_mut(&mut self) -> &mut CliWidget;
 
-    fn get_data(&self) -> CliWidgetData {
-        self.get_widget().data.clone()
+    fn get_data(&self) -> &CliWidgetData {
+        &self.get_widget().data
     }
 
-    fn set_data(&mut self, key: String, text: Vec<String>) {
-        self.get_widget_mut().data.data.insert(key, Some(text));
+    fn state<S: WidgetState + 'static>(&self) -> &S
+    where
+        Self: Sized,
+    {
+        self.get_widget().data.state()
     }
 
-    fn clear_text_data(&mut self, key: String) {
-        self.get_widget_mut().data.data.insert(key, None);
+    fn state_mut<S: WidgetState + 'static>(&mut self) -> &mut S
+    where
+        Self: Sized,
+    {
+        self.get_widget_mut().data.state_mut()
+    }
+
+    fn clear_state(&mut self) {
+        self.get_widget_mut().data.state.clear();
     }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct HeaderWidget {
     pub widget: CliWidget,
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct BodyWidget {
-    black: bool,
     full_screen: bool,
+    line_formatter: Option<LineFormatter>,
+    decorator: Option<Decorator>,
     pub widget: CliWidget,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct CliWidget {
     pub id: CliWidgetId,
     pub title: Option<String>,
     pub data: CliWidgetData,
     pub pos: usize,
-    pub logged_in: bool,
-    is_selected: bool,
+    pub is_selected: bool,
+    /// Fixed scroll offset, or `None` to keep following the end of the content.
+    pub scroll: Option<u16>,
+    /// Set while a followed log view was scrolled away from its end.
+    pub paused: bool,
+    /// Scrolls a table just enough to keep its selected row in view, instead of `scroll`.
+    pub follow_selection: bool,
+    /// The env named in the title, in its colour, while the panes are split.
+    pub env: Option<KubeEnv>,
 }
 
 impl HeaderWidget {
@@ -73,84 +151,124 @@ impl HeaderWidget {
     }
 }
 
+impl Decorator {
+    /// Splits the inner widget area into the rows and the columns of the decorator, one blank
+    /// column apart.
+    fn areas(&self, inner: Rect) -> (Rect, Vec<Rect>) {
+        let widths = (self.widths)(inner.width);
+        let constraints: Vec<Constraint> = std::iter::once(Constraint::Min(0))
+            .chain(
+                widths
+                    .iter()
+                    .flat_map(|width| [Constraint::Length(1), Constraint::Length(*width)]),
+            )
+            .collect();
+        let areas = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(constraints)
+            .split(inner);
+        let columns = areas.iter().skip(2).step_by(2).copied().collect();
+        (areas[0], columns)
+    }
+}
+
 impl BodyWidget {
-    pub fn new(black: bool, full_screen: bool, widget: CliWidget) -> Self {
+    pub fn new(
+        full_screen: bool,
+        line_formatter: Option<LineFormatter>,
+        decorator: Option<Decorator>,
+        widget: CliWidget,
+    ) -> Self {
         BodyWidget {
-            black,
             full_screen,
+            line_formatter,
+            decorator,
             widget,
         }
     }
 }
 
 impl<'a> HeaderWidget {
-    fn kube_info(&self, text: Option<String>) -> Paragraph<'a> {
-        let mut span = Span::default();
-        if let Some(text) = text {
-            if text == "Dev" {
-                span = Span::styled(text, Style::default().fg(Color::Green));
-            } else if text == "Prod" {
-                span = Span::styled(text, Style::default().fg(Color::Red));
-            }
-            Paragraph::new(span)
-                .block(Block::new().borders(Borders::NONE))
-                .alignment(Alignment::Right)
-        } else {
-            Paragraph::new(Span::raw("")).block(Block::new().borders(Borders::NONE))
-        }
-    }
-    fn header_error(&self, text: Option<String>) -> Paragraph<'a> {
-        Paragraph::new(if let Some(error) = text {
-            Span::styled(error, Style::default().fg(Color::Red))
-        } else {
-            Span::styled("All is good", Style::default().fg(Color::LightGreen))
-        })
+    fn kube_info(&self, env: KubeEnv) -> Paragraph<'a> {
+        let colour = match env {
+            KubeEnv::Dev => theme().dev,
+            KubeEnv::Prod => theme().prod,
+        };
+        Paragraph::new(Span::styled(
+            format!("{:?}", env),
+            Style::default().fg(colour),
+        ))
         .block(Block::new().borders(Borders::NONE))
         .alignment(Alignment::Right)
     }
-
-    fn header_login_info(&self, is_logged_in: bool, text: Option<String>) -> Paragraph<'a> {
-        Paragraph::new(if is_logged_in {
+    fn header_namespace(&self, namespace: &str) -> Paragraph<'a> {
+        Paragraph::new(Span::styled(
+            format!("ns: {}", namespace),
+            Style::default().fg(theme().accent),
+        ))
+        .block(Block::new().borders(Borders::NONE))
+        .alignment(Alignment::Left)
+    }
+    fn header_error(&self, header: &HeaderState) -> Paragraph<'a> {
+        let unread = match header.unread_errors {
+            0 => String::new(),
+            count => format!(" [{} unread]", count),
+        };
+        Paragraph::new(if let Some(error) = &header.error {
+            Span::styled(
+                format!("{}{}", error, unread),
+                Style::default().fg(theme().error),
+            )
+        } else if let Some(hint) = &header.hint {
+            Span::styled(hint.to_string(), Style::default().fg(theme().warning))
+        } else if !unread.is_empty() {
             Span::styled(
-                "LOGGED IN".to_string(),
-                Style::default().fg(Color::LightGreen),
+                format!("All is good{}", unread),
+                Style::default().fg(theme().warning),
             )
-        } else if let Some(code) = text {
-            Span::styled(code, Style::default().fg(Color::Yellow))
         } else {
-            Span::styled("busy".to_string(), Style::default().fg(Color::Red))
+            Span::styled("All is good", Style::default().fg(theme().ok))
         })
         .block(Block::new().borders(Borders::NONE))
         .alignment(Alignment::Right)
     }
+
+    fn header_actions(&self, text: String) -> Paragraph<'a> {
+        Paragraph::new(Span::styled(text, Style::default().fg(theme().accent)))
+            .block(Block::new().borders(Borders::NONE))
+            .alignment(Alignment::Left)
+    }
+
+    fn header_login_info(&self) -> Paragraph<'a> {
+        Paragraph::new(Span::styled(
+            "LOGGED IN".to_string(),
+            Style::default().fg(theme().ok),
+        ))
+        .block(Block::new().borders(Borders::NONE))
+        .alignment(Alignment::Right)
+    }
 }
 
 impl<'a> RenderWidget for HeaderWidget {
     fn render(&self, f: &mut Frame, layout: &MainLayoutUI) {
+        let header = self.state::<HeaderState>();
         let rect = layout.get_header_rect(0, f);
-        if let Some(error) = self.widget.data.data.get("error") {
-            f.render_widget(
-                self.header_error(error.as_ref().and_then(|e| Some(e.join("\n")))),
-                rect[0],
-            );
-        }
-        if let Some(login_info) = self.widget.data.data.get("login_info") {
-            if let Some(Some(logged_in)) = self.get_data().data.get("logged in") {
-                f.render_widget(
-                    self.header_login_info(
-                        logged_in[0].eq(true.to_string().as_str()),
-                        login_info.as_ref().and_then(|e| Some(e.join("\n"))),
-                    ),
-                    rect[1],
-                );
-            }
+        f.render_widget(self.header_error(header), rect[0]);
+        if let Some(actions) = header.actions_text(Instant::now()) {
+            f.render_widget(self.header_actions(actions), rect[1]);
+        }
+        if header.auth == AuthStatus::Connected {
+            f.render_widget(self.header_login_info(), rect[2]);
         }
         let rect = layout.get_header_rect(1, f);
-        if let Some(kube_info) = self.widget.data.data.get("kube_info") {
-            f.render_widget(
-                self.kube_info(kube_info.as_ref().and_then(|e| Some(e.join("\n")))),
-                rect[0],
-            );
+        if let Some(env) = header.env {
+            f.render_widget(self.kube_info(env), rect[0]);
+        }
+        if let Some(namespace) = &header.namespace {
+            f.render_widget(self.header_namespace(namespace), rect[0]);
+        }
+        if let Some(split_env) = header.split_env {
+            f.render_widget(self.kube_info(split_env), rect[1]);
         }
     }
 
@@ -163,62 +281,64 @@ impl<'a> RenderWidget for HeaderWidget {
     }
 }
 
+fn env_colour(env: KubeEnv) -> Color {
+    match env {
+        KubeEnv::Dev => theme().dev,
+        KubeEnv::Prod => theme().prod,
+    }
+}
+
 impl<'a> RenderWidget for BodyWidget {
     fn render(&self, f: &mut Frame, layout: &MainLayoutUI) {
-        trace!("rendering widget with data {:?}", self.widget.data.clone());
+        trace!("rendering widget with data {:?}", self.widget.data);
         match self.widget.title.clone() {
             Some(title) => {
-                if let Some(logs) = self.get_data().data.get("logs") {
-                    if self.full_screen {
-                        let rect = layout.get_full_rect(f);
-                        if self.black {
-                            f.render_widget(
-                                self.widget
-                                    .content_in_black(
-                                        title.to_string(),
-                                        logs.clone(),
-                                        rect[self.widget.pos],
-                                    )
-                                    .unwrap_or_default(),
-                                rect[self.widget.pos],
-                            );
-                        } else {
-                            f.render_widget(
-                                self.widget
-                                    .content_in_white(
-                                        title.to_string(),
-                                        self.widget.data.clone().data.get("logs").unwrap().clone(),
-                                        rect[self.widget.pos],
-                                    )
-                                    .unwrap_or_default(),
-                                rect[self.widget.pos],
-                            );
-                        }
+                let title = match self.widget.paused {
+                    true => format!("{} (paused, scroll to the end to follow)", title),
+                    false => title,
+                };
+                let title = match self.widget.env {
+                    Some(env) => Line::from(vec![
+                        Span::styled(format!("{:?} ", env), Style::default().fg(env_colour(env))),
+                        Span::raw(title),
+                    ]),
+                    None => Line::raw(title),
+                };
+                if let Some(logs) = self.widget.data.state.lines() {
+                    let rect = if self.full_screen {
+                        layout.get_full_rect(f)
                     } else {
-                        let rect = layout.get_body_rect(f);
-                        if self.black {
-                            f.render_widget(
-                                self.widget
-                                    .content_in_black(
-                                        title.to_string(),
-                                        logs.clone(),
-                                        rect[self.widget.pos],
-                                    )
-                                    .unwrap_or_default(),
-                                rect[self.widget.pos],
-                            );
-                        } else {
-                            f.render_widget(
+                        layout.get_body_rect(f)
+                    }[self.widget.pos];
+                    if let Some(line_formatter) = self.line_formatter {
+                        let block = self.widget.block(title);
+                        match self.decorator {
+                            Some(decorator) => {
+                                let (rows, columns) = decorator.areas(block.inner(rect));
+                                f.render_widget(block, rect);
+                                f.render_widget(
+                                    self.widget
+                                        .content_formatted(Block::new(), logs, line_formatter, rect)
+                                        .unwrap_or_default(),
+                                    rows,
+                                );
+                                (decorator.draw)(&self.widget.data, f, &columns);
+                            }
+                            None => f.render_widget(
                                 self.widget
-                                    .content_in_white(
-                                        title.to_string(),
-                                        self.widget.data.clone().data.get("logs").unwrap().clone(),
-                                        rect[self.widget.pos],
-                                    )
-                                    .unwrap_or_default(),
-                                rect[self.widget.pos],
-                            );
+                                    .content_formatted(block, logs, line_formatter, rect)
+                                    .unwrap_or_default()
+                                    .wrap(Wrap { trim: false }),
+                                rect,
+                            ),
                         }
+                    } else {
+                        f.render_widget(
+                            self.widget
+                                .content_plain(title, logs, rect)
+                                .unwrap_or_default(),
+                            rect,
+                        );
                     }
                 }
             }
@@ -242,8 +362,11 @@ impl<'a> CliWidget {
             title: Some(title),
             data,
             pos,
-            logged_in: false,
             is_selected: false,
+            scroll: None,
+            paused: false,
+            follow_selection: false,
+            env: None,
         }
     }
 
@@ -253,74 +376,96 @@ impl<'a> CliWidget {
             title: None,
             data,
             pos: 0,
-            logged_in: false,
             is_selected: false,
+            scroll: None,
+            paused: false,
+            follow_selection: false,
+            env: None,
         }
     }
 
-    fn content_in_black(
+    fn content_plain(
         &self,
-        title: String,
-        logs: Option<Vec<String>>,
+        title: Line<'a>,
+        log: Vec<String>,
         rect: Rect,
     ) -> Option<Paragraph<'a>> {
-        let bg_color = Color::Black;
-        let fg_color = Color::White;
-        let border_color = match self.is_selected {
-            true => Color::Red,
-            false => fg_color,
-        };
-        if let Some(log) = logs {
-            Some(
-                Paragraph::new(log.join(""))
-                    .scroll((Self::calculate_scroll(log, rect), 50))
-                    .block(
-                        Block::new()
-                            .title(title)
-                            .borders(Borders::ALL)
-                            .style(Style::new().fg(border_color)),
-                    )
-                    .style(Style::new().fg(fg_color).bg(bg_color))
-                    .alignment(Alignment::Left)
-                    .wrap(Wrap { trim: false }),
-            )
-        } else {
-            None
-        }
+        let theme = theme();
+        let lines: Vec<Line> = log
+            .join("")
+            .lines()
+            .map(|line| Line::styled(line.to_string(), theme.log_line(line)))
+            .collect();
+        Some(
+            Paragraph::new(lines)
+                .scroll((self.scroll_in(log, rect), 0))
+                .block(self.block(title))
+                .style(theme.text())
+                .alignment(Alignment::Left)
+                .wrap(Wrap { trim: false }),
+        )
     }
 
-    fn content_in_white(
+    /// Formats the lines of `log`, scrolled for the widget area `rect`, in `block`. The lines are
+    /// not wrapped, rows of decorated tables stay next to their columns.
+    fn content_formatted(
         &self,
-        title: String,
-        logs: Option<Vec<String>>,
+        block: Block<'a>,
+        log: Vec<String>,
+        line_formatter: LineFormatter,
         rect: Rect,
     ) -> Option<Paragraph<'a>> {
-        if let Some(log) = logs {
-            Some(
-                Paragraph::new(log.join("\n"))
-                    .scroll((Self::calculate_scroll(log, rect), 0))
-                    .block(Block::new().title(title).borders(Borders::ALL))
-                    .style(Style::new().bg(Color::White).fg(Color::Black))
-                    .alignment(Alignment::Left)
-                    .wrap(Wrap { trim: true }),
-            )
-        } else {
-            None
-        }
+        let theme = theme();
+        let lines = self.formatted_lines(&log, line_formatter);
+        let plain_lines = plain_lines(&lines);
+        Some(
+            Paragraph::new(lines)
+                .scroll((self.scroll_in(plain_lines, rect), 0))
+                .block(block)
+                .style(theme.text())
+                .alignment(Alignment::Left),
+        )
+    }
+
+    fn block(&self, title: Line<'a>) -> Block<'a> {
+        Block::new()
+            .title(title)
+            .borders(Borders::ALL)
+            .style(theme().border(self.is_selected))
+    }
+
+    fn formatted_lines(&self, log: &[String], line_formatter: LineFormatter) -> Vec<Line<'a>> {
+        log.iter()
+            .flat_map(|entry| entry.lines())
+            .filter_map(|line| line_formatter(&self.data, line))
+            .collect()
+    }
+
+    fn scroll_in(&self, lines: Vec<String>, rect: Rect) -> u16 {
+        self.selection_scroll(rect)
+            .or(self.scroll)
+            .unwrap_or_else(|| Self::calculate_scroll(lines, rect))
+    }
+
+    /// The scroll offset keeping the selected row in view in `rect`, when the widget follows the
+    /// selection of its table.
+    pub fn selection_scroll(&self, rect: Rect) -> Option<u16> {
+        let table = self.data.state.table().filter(|_| self.follow_selection)?;
+        Some(table.scroll_to_selection(Block::new().borders(Borders::ALL).inner(rect).height))
     }
 
     fn calculate_scroll(lines: Vec<String>, estate: Rect) -> u16 {
         let mut scroll_to: u16 = 0;
         for line in lines {
             let new_lines = line.chars().filter(|c| c.eq(&'\n')).count();
-            let estate_space = line.len() as u16 / estate.width;
+            let estate_space = line.len() as u16 / estate.width.max(1);
             if new_lines as u16 > estate_space {
                 scroll_to += new_lines as u16 + 1;
             } else {
                 scroll_to += estate_space + 1;
             }
         }
-        let height = estate.height - 4;
+        let height = estate.height.saturating_sub(4);
         if height > scroll_to {
             scroll_to = 0;
         } else {
@@ -330,36 +475,103 @@ impl<'a> CliWidget {
     }
 }
 
-fn add_to_widget_data<'a>(widget: &mut BodyWidget, text: String) -> &mut BodyWidget {
-    if let Some(Some(existing_text)) = &mut widget.get_data().data.get_mut("logs") {
-        existing_text.push(text);
-        widget.set_data("logs".to_string(), existing_text.to_vec());
+fn plain_lines(lines: &[Line]) -> Vec<String> {
+    lines
+        .iter()
+        .map(|line| {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect()
+        })
+        .collect()
+}
+
+impl BodyWidget {
+    /// The log shown by the logs widget.
+    pub fn current_log(&self) -> &LogState {
+        self.state::<LogTabsState>().current()
+    }
+
+    pub fn current_log_mut(&mut self) -> &mut LogState {
+        self.state_mut::<LogTabsState>().current_mut()
+    }
+
+    /// The scroll offset that shows the end of the content in `rect`, used while following.
+    pub fn follow_scroll(&self, rect: Rect) -> u16 {
+        let Some(log) = self.widget.data.state.lines() else {
+            return 0;
+        };
+        match self.line_formatter {
+            Some(line_formatter) => CliWidget::calculate_scroll(
+                plain_lines(&self.widget.formatted_lines(&log, line_formatter)),
+                rect,
+            ),
+            None => CliWidget::calculate_scroll(log, rect),
+        }
+    }
+}
+
+/// Lines of the history paged in at once when scrolling above the first line.
+const HISTORY_PAGE_LINES: usize = 500;
+
+/// Scrolls a log view by `lines`, `follow` being the offset that shows its end. Scrolling up
+/// pauses following new lines, scrolling back down to the end follows them again. Scrolling
+/// above the first line pages in older lines of its history.
+pub fn scroll_log_view(widget: &mut BodyWidget, lines: i32, follow: u16) {
+    let current = widget.get_widget().scroll.unwrap_or(follow) as i32;
+    let mut next = current + lines;
+    let mut follow = follow as i32;
+    if let Some(log) = widget.get_widget_mut().data.state.log_mut() {
+        if next < 0 && follow + (HISTORY_PAGE_LINES as i32) < u16::MAX as i32 {
+            let paged = log.page_in(HISTORY_PAGE_LINES) as i32;
+            next += paged;
+            follow += paged;
+        }
+        if next >= follow {
+            log.page_out();
+        }
+    }
+    let next = next.max(0);
+    if next >= follow {
+        widget.get_widget_mut().scroll = None;
+        widget.get_widget_mut().paused = false;
     } else {
-        widget.set_data("logs".to_string(), vec![text]);
+        widget.get_widget_mut().scroll = Some(next as u16);
+        widget.get_widget_mut().paused = true;
+    }
+}
+
+/// Scrolls a view by `lines`, no further than `last`, the offset that shows its end.
+pub fn scroll_view(widget: &mut BodyWidget, lines: i32, last: u16) {
+    let current = widget.get_widget().scroll.unwrap_or(0) as i32;
+    widget.get_widget_mut().scroll = Some((current + lines).clamp(0, last as i32) as u16);
+}
+
+/// Selects the row shown on `line` of a table, the header line selects nothing.
+pub fn select_row_at(widget: &mut BodyWidget, line: usize) {
+    if let Some(table) = widget.get_widget_mut().data.state.table_mut() {
+        table.select_line(line);
     }
-    widget
 }
 
 pub fn create_header_widget_data<'a>() -> WidgetDescription<HeaderWidget> {
-    let header_data = CliWidgetData::new(CliWidgetId::Header);
+    let header_data = CliWidgetData::new(HeaderState::default());
     let header_widget = HeaderWidget::new(CliWidget::unbordered(CliWidgetId::Header, header_data));
     WidgetDescription {
         widget: header_widget,
         event_handler: |_, _| None,
-        keymap: |_, _, _| {},
+        commands: vec![],
+        keymap: |_, _, _| Some(()),
     }
 }
 
 pub fn create_login_widget_data<'a>() -> WidgetDescription<BodyWidget> {
-    let login_widget_data = CliWidgetData {
-        id: CliWidgetId::GetLoginLogs,
-        thread_started: false,
-        initiate_thread: None,
-        data: HashMap::default(),
-    };
+    let login_widget_data = CliWidgetData::new(LogState::new(widget_truncator("login")));
     let login_widget = BodyWidget::new(
-        false,
         true,
+        None,
+        None,
         CliWidget::bordered(
             CliWidgetId::GetLoginLogs,
             "Logging in...".to_string(),
@@ -369,7 +581,12 @@ pub fn create_login_widget_data<'a>() -> WidgetDescription<BodyWidget> {
     );
     let login_event_handler = |event: &TUIEvent, store: &mut Store| match event {
         TUIEvent::AddLoginLog(log_part) => {
-            add_to_widget_data(store.login_widget.as_mut().unwrap(), log_part.to_string());
+            store
+                .login_widget
+                .as_mut()
+                .unwrap()
+                .state_mut::<LogState>()
+                .push(log_part);
             None
         }
         _ => Some(()),
@@ -377,32 +594,100 @@ pub fn create_login_widget_data<'a>() -> WidgetDescription<BodyWidget> {
     WidgetDescription {
         widget: login_widget,
         event_handler: login_event_handler,
-        keymap: |_, _, _| {},
+        commands: vec![],
+        keymap: |_, _, _| Some(()),
     }
 }
 
+const LOGS_TITLE: &str = "Salespoint Logs";
+
 pub fn create_logs_widget_data<'a>() -> WidgetDescription<BodyWidget> {
-    let logs_widget_data = CliWidgetData {
-        id: CliWidgetId::GetLogs,
-        thread_started: false,
-        initiate_thread: Some(|a| {
+    let logs = LogTabsState::new(log_sources(), || {
+        let mut log = LogState::new(widget_truncator("logs"));
+        if let Some(history) = widget_history("logs", true) {
+            log.spill_to(history);
+        }
+        log
+    });
+    let logs_widget_data = CliWidgetData::with_thread(
+        |a| {
             a.send(TUIAction::GetLogs).unwrap();
-        }),
-        data: HashMap::default(),
-    };
+        },
+        logs,
+    );
     let logs_widget = BodyWidget::new(
-        true,
         false,
+        Some(search_line),
+        None,
         CliWidget::bordered(
             CliWidgetId::GetLogs,
-            "Salespoint Logs".to_string(),
+            LOGS_TITLE.to_string(),
             0,
             logs_widget_data,
         ),
     );
     let logs_event_handler = |event: &TUIEvent, store: &mut Store| match event {
-        TUIEvent::AddLog(log_part) => {
-            add_to_widget_data(store.logs_widget.as_mut().unwrap(), log_part.to_string());
+        TUIEvent::AddLog(source, log_part) => {
+            store
+                .logs_widget
+                .as_mut()
+                .unwrap()
+                .state_mut::<LogTabsState>()
+                .push(*source, log_part);
+            None
+        }
+        TUIEvent::LogStreamStatus(source, status) => {
+            let logs_widget = store.logs_widget.as_mut().unwrap();
+            let tabs = logs_widget.state_mut::<LogTabsState>();
+            if let Some(current) = tabs.statuses.get_mut(*source) {
+                *current = status.clone();
+            }
+            refresh_logs_title(logs_widget);
+            None
+        }
+        TUIEvent::MoveLogTab(offset) => {
+            let logs_widget = store.logs_widget.as_mut().unwrap();
+            logs_widget.current_log_mut().page_out();
+            logs_widget.state_mut::<LogTabsState>().move_tab(*offset);
+            logs_widget.get_widget_mut().scroll = None;
+            logs_widget.get_widget_mut().paused = false;
+            refresh_logs_title(logs_widget);
+            None
+        }
+        TUIEvent::OpenLogSearch => {
+            let logs_widget = store.logs_widget.as_mut().unwrap();
+            logs_widget.current_log_mut().search = Some(LogSearch::default());
+            refresh_logs_title(logs_widget);
+            None
+        }
+        TUIEvent::SetLogSearch(query) => {
+            let logs_widget = store.logs_widget.as_mut().unwrap();
+            if let Some(search) = &mut logs_widget.current_log_mut().search {
+                *search = LogSearch {
+                    query: query.clone(),
+                    ..LogSearch::default()
+                };
+            }
+            refresh_logs_title(logs_widget);
+            None
+        }
+        TUIEvent::FindInLogs => {
+            find_in_logs(store.logs_widget.as_mut().unwrap());
+            None
+        }
+        TUIEvent::CloseLogSearch => {
+            let logs_widget = store.logs_widget.as_mut().unwrap();
+            logs_widget.current_log_mut().search = None;
+            refresh_logs_title(logs_widget);
+            None
+        }
+        TUIEvent::CycleLogTime => {
+            let tabs = store
+                .logs_widget
+                .as_mut()
+                .unwrap()
+                .state_mut::<LogTabsState>();
+            tabs.time = tabs.time.next();
             None
         }
         _ => Some(()),
@@ -410,22 +695,188 @@ pub fn create_logs_widget_data<'a>() -> WidgetDescription<BodyWidget> {
     WidgetDescription {
         widget: logs_widget,
         event_handler: logs_event_handler,
-        keymap: |_, _, _| {},
+        commands: vec![
+            Command::new(
+                "restart-logs",
+                "Restart following the logs",
+                |_, event_tx| {
+                    event_tx
+                        .send(TUIEvent::RunAction(TUIAction::GetLogs))
+                        .unwrap()
+                },
+            ),
+            Command::new(
+                "next-log-tab",
+                "Show the logs of the next source",
+                |_, event_tx| event_tx.send(TUIEvent::MoveLogTab(1)).unwrap(),
+            ),
+            Command::new(
+                "previous-log-tab",
+                "Show the logs of the previous source",
+                |_, event_tx| event_tx.send(TUIEvent::MoveLogTab(-1)).unwrap(),
+            ),
+            Command::new(
+                "search-logs",
+                "Search the logs, going back from the newest line",
+                |_, event_tx| event_tx.send(TUIEvent::OpenLogSearch).unwrap(),
+            ),
+            Command::new(
+                "export-logs",
+                "Export the logs kept in memory and on disk to a file",
+                |_, event_tx| event_tx.send(TUIEvent::ExportLogs).unwrap(),
+            ),
+            Command::new(
+                "cycle-log-time",
+                "Show log timestamps as received, in local time or relative to now",
+                |_, event_tx| event_tx.send(TUIEvent::CycleLogTime).unwrap(),
+            ),
+        ],
+        keymap: |keycode: KeyCode, store: &Store, event_tx: &Sender<TUIEvent>| {
+            let logs_widget = store.logs_widget.as_ref().unwrap();
+            let Some(search) = &logs_widget.current_log().search else {
+                return Some(());
+            };
+            let event = match keycode {
+                KeyCode::Esc => TUIEvent::CloseLogSearch,
+                KeyCode::Enter => TUIEvent::FindInLogs,
+                KeyCode::Backspace => {
+                    let mut chars = search.query.chars();
+                    chars.next_back();
+                    TUIEvent::SetLogSearch(chars.as_str().to_string())
+                }
+                KeyCode::Char(c) => TUIEvent::SetLogSearch(format!("{}{}", search.query, c)),
+                // the search is typed, other keys do nothing
+                _ => return None,
+            };
+            event_tx.send(event).unwrap();
+            None
+        },
     }
 }
 
-pub fn create_pods_widget_data<'a>() -> WidgetDescription<BodyWidget> {
-    let pods_widget_data = CliWidgetData {
-        id: CliWidgetId::GetPods,
-        thread_started: false,
-        initiate_thread: Some(|a| {
-            a.send(TUIAction::GetPods).unwrap();
-        }),
-        data: HashMap::default(),
+/// Names the tabs, the shown one in brackets, the trouble following them and the search typed
+/// in the shown one.
+fn refresh_logs_title(logs_widget: &mut BodyWidget) {
+    let tabs = logs_widget.state::<LogTabsState>();
+    let names = tabs.tab_names();
+    let title = match names.len() {
+        1 => LOGS_TITLE.to_string(),
+        _ => {
+            let names: Vec<String> = names
+                .iter()
+                .enumerate()
+                .map(|(tab, name)| match tab == tabs.tab {
+                    true => format!("[{}]", name),
+                    false => name.to_string(),
+                })
+                .collect();
+            format!("Logs: {}", names.join(" "))
+        }
     };
-    let pods_widget = BodyWidget::new(
-        true,
+    let statuses: Vec<String> = tabs
+        .sources
+        .iter()
+        .zip(&tabs.statuses)
+        .filter_map(|(source, status)| Some(format!("{}: {}", source.name, status.as_ref()?)))
+        .collect();
+    let title = match statuses.is_empty() {
+        true => title,
+        false => format!("{} ({})", title, statuses.join(", ")),
+    };
+    let title = match &tabs.current().search {
+        Some(search) if search.exhausted => format!(
+            "{} (search: {}, no older match, esc to stop)",
+            title, search.query
+        ),
+        Some(search) => format!(
+            "{} (search: {}, enter for the previous match, esc to stop)",
+            title, search.query
+        ),
+        None => title,
+    };
+    logs_widget.get_widget_mut().title = Some(title);
+}
+
+/// Shows the match of the search before the last one, paging older lines in from disk.
+fn find_in_logs(logs_widget: &mut BodyWidget) {
+    let logs = logs_widget.current_log_mut();
+    let Some(search) = logs.search.clone() else {
+        return;
+    };
+    if search.query.is_empty() {
+        return;
+    }
+    let before = search.matched.unwrap_or_else(|| logs.end());
+    let found = logs.find_before(&search.query, before);
+    let shown = found.map(|line| logs.show(line));
+    logs.search = Some(LogSearch {
+        matched: found.or(search.matched),
+        exhausted: found.is_none(),
+        ..search
+    });
+    if let Some(shown) = shown {
+        logs_widget.get_widget_mut().scroll = Some(shown.min(u16::MAX as usize) as u16);
+        logs_widget.get_widget_mut().paused = true;
+    }
+    refresh_logs_title(logs_widget);
+}
+
+/// Colours a log line by its level and its source prefix by the source, reversing the lines
+/// matching the search.
+fn search_line(data: &CliWidgetData, line: &str) -> Option<Line<'static>> {
+    let tabs = data.state::<LogTabsState>();
+    let shown = tabs.time.show(line);
+    let mut style = theme().log_line(line);
+    if let Some(search) = &tabs.current().search {
+        if !search.query.is_empty() && line.contains(&search.query) {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+    }
+    let prefix = tabs
+        .sources
+        .iter()
+        .enumerate()
+        .filter(|_| tabs.all_shown())
+        .find_map(|(index, source)| {
+            let prefix = format!("[{}] ", source.name);
+            line.starts_with(&prefix).then_some((index, prefix))
+        });
+    let body_start = prefix.as_ref().map_or(0, |(_, prefix)| prefix.len());
+    if line[body_start..].starts_with(POD_MARKER) {
+        style = style.add_modifier(Modifier::BOLD);
+    }
+    Some(match prefix {
+        Some((index, prefix)) => Line::from(vec![
+            Span::styled(prefix.clone(), style.patch(theme().source(index))),
+            Span::styled(shown[prefix.len()..].to_string(), style),
+        ]),
+        None => Line::styled(shown.into_owned(), style),
+    })
+}
+
+pub fn create_pods_widget_data<'a>() -> WidgetDescription<BodyWidget> {
+    let pods_widget_data = CliWidgetData::with_thread(
+        |a| {
+            a.send(TUIAction::Refresh(
+                Box::new(TUIAction::GetPods),
+                Duration::from_secs(10),
+            ))
+            .unwrap();
+            a.send(TUIAction::Refresh(
+                Box::new(TUIAction::GetTop),
+                Duration::from_secs(config().get_or("top", "interval_secs", 10)),
+            ))
+            .unwrap();
+        },
+        PodsState::default(),
+    );
+    let mut pods_widget = BodyWidget::new(
         false,
+        Some(pods_line),
+        Some(Decorator {
+            widths: usage_widths,
+            draw: usage_sparklines,
+        }),
         CliWidget::bordered(
             CliWidgetId::GetPods,
             "Salespoint pods".to_string(),
@@ -433,32 +884,96 @@ pub fn create_pods_widget_data<'a>() -> WidgetDescription<BodyWidget> {
             pods_widget_data,
         ),
     );
+    pods_widget.widget.is_selected = true;
+    // the usage sparklines drawn next to the rows scroll along, see `usage_sparklines`
+    pods_widget.widget.follow_selection = true;
     let pods_event_handler = |event: &TUIEvent, store: &mut Store| match event {
         TUIEvent::AddPods(pods) => {
-            add_to_widget_data(store.pods_widget.as_mut().unwrap(), pods.to_string());
+            store
+                .pods_widget
+                .as_mut()
+                .unwrap()
+                .state_mut::<PodsState>()
+                .table
+                .set_rows(pods);
             None
         }
+        TUIEvent::AddTop(top) => {
+            let pods = store.pods_widget.as_mut().unwrap().state_mut::<PodsState>();
+            let history = config().get_or("top", "history", 30);
+            let mut sampled = HashSet::new();
+            for line in top.lines() {
+                let columns: Vec<&str> = line.split_whitespace().collect();
+                if let [pod, cpu, memory, ..] = columns[..] {
+                    sampled.insert(pod.to_string());
+                    let usage = pods.usage.entry(pod.to_string()).or_default();
+                    usage.cpu.push(parse_cpu_millis(cpu).unwrap_or(0));
+                    usage
+                        .memory
+                        .push(parse_memory_mebibytes(memory).unwrap_or(0));
+                    for samples in [&mut usage.cpu, &mut usage.memory] {
+                        let overflow = samples.len().saturating_sub(history);
+                        samples.drain(..overflow);
+                    }
+                }
+            }
+            // the pods gone since
+            pods.usage.retain(|pod, _| sampled.contains(pod));
+            None
+        }
+        TUIEvent::AddPodLimits(limits) => {
+            let pods = store.pods_widget.as_mut().unwrap().state_mut::<PodsState>();
+            for line in limits.lines().filter(|line| !line.starts_with("NAME ")) {
+                if let [pod, limits, containers] = line.split_whitespace().collect::<Vec<_>>()[..] {
+                    pods.usage.entry(pod.to_string()).or_default().limit =
+                        memory_limit(limits, containers);
+                }
+            }
+            None
+        }
+        TUIEvent::Navigate(direction) => {
+            let pods_widget = store.pods_widget.as_mut().unwrap();
+            if pods_widget.get_widget().is_selected {
+                pods_widget
+                    .state_mut::<PodsState>()
+                    .table
+                    .move_selection(direction);
+            }
+            // other widgets may follow the pod selection
+            Some(())
+        }
         _ => Some(()),
     };
     WidgetDescription {
         widget: pods_widget,
         event_handler: pods_event_handler,
-        keymap: |_, _, _| {},
+        commands: vec![
+            Command::new(
+                "describe-pod",
+                "Describe the selected pod",
+                |_, event_tx| event_tx.send(TUIEvent::DescribePod).unwrap(),
+            ),
+            Command::new("refresh-pods", "Refresh the pods", |_, event_tx| {
+                event_tx
+                    .send(TUIEvent::RunAction(TUIAction::GetPods))
+                    .unwrap()
+            }),
+        ],
+        keymap: |_, _, _| Some(()),
     }
 }
 
 pub fn create_tail_widget_data<'a>() -> WidgetDescription<BodyWidget> {
-    let tail_widget_data = CliWidgetData {
-        id: CliWidgetId::Tail,
-        thread_started: false,
-        initiate_thread: Some(|a| {
+    let tail_widget_data = CliWidgetData::with_thread(
+        |a| {
             a.send(TUIAction::GetTail).unwrap();
-        }),
-        data: HashMap::default(),
-    };
+        },
+        LogState::new(widget_truncator("tail")),
+    );
     let tail_widget = BodyWidget::new(
-        true,
         false,
+        None,
+        None,
         CliWidget::bordered(
             CliWidgetId::Tail,
             "cli logs".to_string(),
@@ -468,7 +983,12 @@ pub fn create_tail_widget_data<'a>() -> WidgetDescription<BodyWidget> {
     );
     let tail_event_handler = |event: &TUIEvent, store: &mut Store| match event {
         TUIEvent::AddTailLog(tail_log) => {
-            add_to_widget_data(store.tail_widget.as_mut().unwrap(), tail_log.to_string());
+            store
+                .tail_widget
+                .as_mut()
+                .unwrap()
+                .state_mut::<LogState>()
+                .push(tail_log);
             None
         }
         _ => Some(()),
@@ -476,65 +996,941 @@ pub fn create_tail_widget_data<'a>() -> WidgetDescription<BodyWidget> {
     WidgetDescription {
         widget: tail_widget,
         event_handler: tail_event_handler,
-        keymap: |_, _, _| {},
+        commands: vec![],
+        keymap: |_, _, _| Some(()),
     }
 }
 
-pub fn _create_login_request_widget_data<'a>() -> WidgetDescription<BodyWidget> {
-    let login_request_widget_data = CliWidgetData {
-        id: CliWidgetId::Tail,
-        thread_started: false,
-        initiate_thread: Some(|_| {}),
-        data: HashMap::default(),
+pub fn create_events_widget_data() -> WidgetDescription<BodyWidget> {
+    let events_widget_data = CliWidgetData::with_thread(
+        |a| {
+            a.send(TUIAction::GetEvents).unwrap();
+        },
+        EventsState {
+            log: LogState::new(widget_truncator("events")),
+            pod: None,
+        },
+    );
+    let events_widget = BodyWidget::new(
+        false,
+        Some(events_line),
+        None,
+        CliWidget::bordered(
+            CliWidgetId::GetEvents,
+            "Events".to_string(),
+            3,
+            events_widget_data,
+        ),
+    );
+    let events_event_handler = |event: &TUIEvent, store: &mut Store| match event {
+        TUIEvent::AddEvents(events) => {
+            store
+                .events_widget
+                .as_mut()
+                .unwrap()
+                .state_mut::<EventsState>()
+                .log
+                .push(events);
+            None
+        }
+        TUIEvent::ToggleEventsFilter => {
+            let selected = selected_row(store.pods_widget.as_ref().unwrap().get_data());
+            let events_widget = store.events_widget.as_mut().unwrap();
+            match (&events_widget.state::<EventsState>().pod, selected) {
+                (Some(_), _) | (_, None) => {
+                    events_widget.state_mut::<EventsState>().pod = None;
+                    events_widget.get_widget_mut().title = Some("Events".to_string());
+                }
+                (None, Some(pod)) => {
+                    events_widget.get_widget_mut().title = Some(format!("Events for {}", pod));
+                    events_widget.state_mut::<EventsState>().pod = Some(pod);
+                }
+            }
+            None
+        }
+        TUIEvent::Navigate(_) => {
+            let selected = selected_row(store.pods_widget.as_ref().unwrap().get_data());
+            let events_widget = store.events_widget.as_mut().unwrap();
+            if let (Some(_), Some(pod)) = (&events_widget.state::<EventsState>().pod, selected) {
+                events_widget.get_widget_mut().title = Some(format!("Events for {}", pod));
+                events_widget.state_mut::<EventsState>().pod = Some(pod);
+            }
+            Some(())
+        }
+        _ => Some(()),
     };
-    let login_request_widget = BodyWidget::new(
+    WidgetDescription {
+        widget: events_widget,
+        event_handler: events_event_handler,
+        commands: vec![Command::new(
+            "filter-events",
+            "Toggle showing only events of the selected pod",
+            |_, event_tx| event_tx.send(TUIEvent::ToggleEventsFilter).unwrap(),
+        )],
+        keymap: |_, _, _| Some(()),
+    }
+}
+
+pub fn create_describe_widget_data() -> WidgetDescription<BodyWidget> {
+    let describe_widget_data = CliWidgetData::new(DescribeState::default());
+    let describe_widget = BodyWidget::new(
         true,
-        false,
+        Some(describe_line),
+        None,
         CliWidget::bordered(
-            CliWidgetId::LoginRequest,
-            "bleoboeli".to_string(),
-            1,
-            login_request_widget_data,
+            CliWidgetId::DescribePod,
+            "Describe pod".to_string(),
+            0,
+            describe_widget_data,
         ),
     );
-    let login_request_event_handler = |event: &TUIEvent, store: &mut Store| match event {
-        TUIEvent::RequestLoginStart => {
-            store.request_login = true;
+    let describe_event_handler = |event: &TUIEvent, store: &mut Store| match event {
+        TUIEvent::AddPodDescription(description) => {
+            let describe_widget = store.describe_widget.as_mut().unwrap();
+            let description: Vec<String> =
+                description.lines().map(|line| line.to_string()).collect();
+            let pod = description
+                .iter()
+                .find_map(|line| line.strip_prefix("Name:"))
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            describe_widget.get_widget_mut().title = Some(format!(
+                "pod/{} (Tab: next section, Enter: collapse, Esc: close)",
+                pod
+            ));
+            describe_widget.get_widget_mut().scroll = Some(0);
+            *describe_widget.state_mut::<DescribeState>() = DescribeState {
+                description,
+                ..DescribeState::default()
+            };
+            refresh_description(describe_widget.state_mut());
             None
         }
-        TUIEvent::RequestLoginStop => {
-            store.request_login = false;
+        TUIEvent::ClosePodDescription => {
+            store.describe_widget.as_mut().unwrap().clear_state();
+            None
+        }
+        TUIEvent::NextDescribeSection => {
+            let describe_widget = store.describe_widget.as_mut().unwrap();
+            let describe = describe_widget.state_mut::<DescribeState>();
+            let sections = describe_sections(describe);
+            let next = match &describe.section {
+                Some(section) => sections
+                    .iter()
+                    .position(|name| name == section)
+                    .map(|index| (index + 1) % sections.len())
+                    .unwrap_or(0),
+                None => 0,
+            };
+            if let Some(section) = sections.get(next) {
+                describe.section = Some(section.clone());
+                let line = describe
+                    .visible
+                    .iter()
+                    .flatten()
+                    .position(|line| section_name(line) == Some(section.as_str()))
+                    .unwrap_or(0);
+                describe_widget.get_widget_mut().scroll = Some(line as u16);
+            }
+            None
+        }
+        TUIEvent::ToggleDescribeSection => {
+            let describe = store
+                .describe_widget
+                .as_mut()
+                .unwrap()
+                .state_mut::<DescribeState>();
+            if let Some(section) = describe.section.clone() {
+                match describe.collapsed.iter().position(|name| *name == section) {
+                    Some(index) => {
+                        describe.collapsed.remove(index);
+                    }
+                    None => describe.collapsed.push(section),
+                }
+                refresh_description(describe);
+            }
             None
         }
+        TUIEvent::Navigate(_) => {
+            // the overlay is modal, it scrolls with `ScrollWidget` as only the UI knows its height
+            match store
+                .describe_widget
+                .as_ref()
+                .unwrap()
+                .state::<DescribeState>()
+                .visible
+            {
+                Some(_) => None,
+                None => Some(()),
+            }
+        }
         _ => Some(()),
     };
     WidgetDescription {
-        widget: login_request_widget,
-        event_handler: login_request_event_handler,
+        widget: describe_widget,
+        event_handler: describe_event_handler,
+        commands: vec![],
         keymap: |keycode: KeyCode, store: &Store, event_tx: &Sender<TUIEvent>| {
-            if store.request_login {
+            let describe_widget = store.describe_widget.as_ref().unwrap();
+            if describe_widget.state::<DescribeState>().visible.is_some() {
                 match keycode {
-                    KeyCode::Char('1') => {
-                        event_tx.send(TUIEvent::RequestLoginStop).unwrap();
-                        event_tx.send(TUIEvent::ClearError).unwrap();
-                        event_tx.send(TUIEvent::CheckConnectivity).unwrap();
+                    KeyCode::Esc => event_tx.send(TUIEvent::ClosePodDescription).unwrap(),
+                    KeyCode::Tab => event_tx.send(TUIEvent::NextDescribeSection).unwrap(),
+                    KeyCode::Enter => event_tx.send(TUIEvent::ToggleDescribeSection).unwrap(),
+                    _ => {}
+                }
+                // the overlay is modal, keys never reach the widgets behind it
+                None
+            } else {
+                Some(())
+            }
+        },
+    }
+}
+
+/// A top level `kubectl describe` line without a value, like `Containers:`, starts a section.
+fn section_name(line: &str) -> Option<&str> {
+    let line = line.trim_start_matches(['▾', '▸', ' ']);
+    let line = line.split(" (").next().unwrap_or(line);
+    let name = line.trim_end().strip_suffix(':')?;
+    if name.is_empty() || name.contains(':') {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+fn describe_sections(describe: &DescribeState) -> Vec<String> {
+    describe
+        .description
+        .iter()
+        .filter(|line| !line.starts_with(char::is_whitespace))
+        .filter_map(|line| section_name(line))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Rebuilds the visible description lines, folding the collapsed sections into their header.
+fn refresh_description(describe: &mut DescribeState) {
+    let mut visible = vec![];
+    let mut hidden_lines: Option<usize> = None;
+    for line in describe.description.iter().cloned() {
+        let top_level = !line.starts_with(char::is_whitespace);
+        if let (Some(count), true) = (hidden_lines, top_level) {
+            if let Some(header) = visible.last_mut() {
+                *header = format!("{} ({} lines)", header, count);
+            }
+            hidden_lines = None;
+        }
+        if let Some(count) = hidden_lines.as_mut() {
+            *count += 1;
+            continue;
+        }
+        match section_name(&line).filter(|_| top_level) {
+            Some(name) if describe.collapsed.iter().any(|section| section == name) => {
+                visible.push(format!("▸ {}", line));
+                hidden_lines = Some(0);
+            }
+            Some(_) => visible.push(format!("▾ {}", line)),
+            None => visible.push(line),
+        }
+    }
+    if let (Some(count), Some(header)) = (hidden_lines, visible.last_mut()) {
+        *header = format!("{} ({} lines)", header, count);
+    }
+    describe.visible = Some(visible);
+}
+
+fn describe_line(data: &CliWidgetData, line: &str) -> Option<Line<'static>> {
+    if line.starts_with(['▾', '▸']) {
+        let focused = data.state::<DescribeState>().section.as_deref() == section_name(line);
+        let style = Style::new().add_modifier(Modifier::BOLD).fg(theme().accent);
+        if focused {
+            Some(Line::styled(
+                line.to_string(),
+                style.add_modifier(Modifier::REVERSED),
+            ))
+        } else {
+            Some(Line::styled(line.to_string(), style))
+        }
+    } else {
+        Some(Line::raw(line.to_string()))
+    }
+}
+
+pub fn create_deployments_widget_data() -> WidgetDescription<BodyWidget> {
+    let deployments_widget_data = CliWidgetData::with_thread(
+        |a| {
+            a.send(TUIAction::Refresh(
+                Box::new(TUIAction::GetDeployments),
+                Duration::from_secs(10),
+            ))
+            .unwrap();
+        },
+        TableState::default(),
+    );
+    let deployments_widget = BodyWidget::new(
+        false,
+        Some(deployments_line),
+        None,
+        CliWidget::bordered(
+            CliWidgetId::GetDeployments,
+            "Deployments".to_string(),
+            2,
+            deployments_widget_data,
+        ),
+    );
+    let deployments_event_handler = |event: &TUIEvent, store: &mut Store| match event {
+        TUIEvent::AddDeployments(deployments) => {
+            store
+                .deployments_widget
+                .as_mut()
+                .unwrap()
+                .state_mut::<TableState>()
+                .set_rows(deployments);
+            None
+        }
+        TUIEvent::Navigate(direction) => {
+            let deployments_widget = store.deployments_widget.as_mut().unwrap();
+            if deployments_widget.get_widget().is_selected {
+                deployments_widget
+                    .state_mut::<TableState>()
+                    .move_selection(direction);
+            }
+            Some(())
+        }
+        _ => Some(()),
+    };
+    WidgetDescription {
+        widget: deployments_widget,
+        event_handler: deployments_event_handler,
+        commands: vec![
+            Command::new(
+                "refresh-deployments",
+                "Refresh the deployments",
+                |_, event_tx| {
+                    event_tx
+                        .send(TUIEvent::RunAction(TUIAction::GetDeployments))
+                        .unwrap()
+                },
+            ),
+            Command::new(
+                "undo-rollout",
+                "Roll the selected deployment back",
+                |store, event_tx| {
+                    // only from the deployments widget, the deployment it shows selected is undone
+                    if store
+                        .deployments_widget
+                        .as_ref()
+                        .unwrap()
+                        .get_widget()
+                        .is_selected
+                    {
+                        event_tx.send(TUIEvent::RequestRolloutUndo).unwrap()
                     }
-                    KeyCode::Char('2') => {
-                        event_tx.send(TUIEvent::RequestLoginStop).unwrap();
-                        event_tx.send(TUIEvent::NeedsLogin).unwrap()
+                },
+            ),
+        ],
+        keymap: |keycode: KeyCode, store: &Store, event_tx: &Sender<TUIEvent>| {
+            if store.pending_rollout_undo.is_some() {
+                match keycode {
+                    KeyCode::Char('y') => event_tx.send(TUIEvent::ConfirmRolloutUndo).unwrap(),
+                    KeyCode::Char('n') | KeyCode::Esc => {
+                        event_tx.send(TUIEvent::CancelRolloutUndo).unwrap()
                     }
                     _ => {}
                 }
+                // waiting for confirmation, other keys are ignored
+                None
+            } else {
+                Some(())
             }
         },
     }
 }
 
+pub fn create_split_logs_widget_data() -> WidgetDescription<BodyWidget> {
+    let split_logs_widget = BodyWidget::new(
+        false,
+        None,
+        None,
+        CliWidget::bordered(
+            CliWidgetId::SplitLogs,
+            "Salespoint Logs".to_string(),
+            4,
+            CliWidgetData::new(LogState::new(widget_truncator("split_logs"))),
+        ),
+    );
+    let split_logs_event_handler = |event: &TUIEvent, store: &mut Store| match event {
+        TUIEvent::AddSplitLog(env, log_part) => {
+            // lines of a pane that was closed or switched to another env are dropped
+            if store.split_env == Some(*env) {
+                store
+                    .split_logs_widget
+                    .as_mut()
+                    .unwrap()
+                    .state_mut::<LogState>()
+                    .push(log_part);
+            }
+            None
+        }
+        _ => Some(()),
+    };
+    WidgetDescription {
+        widget: split_logs_widget,
+        event_handler: split_logs_event_handler,
+        commands: vec![Command::new(
+            "toggle-split",
+            "Compare with the other env side by side",
+            |_, event_tx| event_tx.send(TUIEvent::ToggleSplit).unwrap(),
+        )],
+        keymap: |_, _, _| Some(()),
+    }
+}
+
+pub fn create_split_pods_widget_data() -> WidgetDescription<BodyWidget> {
+    let mut split_pods_widget = BodyWidget::new(
+        false,
+        Some(selectable_line),
+        None,
+        CliWidget::bordered(
+            CliWidgetId::SplitPods,
+            "Salespoint pods".to_string(),
+            5,
+            CliWidgetData::new(TableState::default()),
+        ),
+    );
+    split_pods_widget.widget.follow_selection = true;
+    let split_pods_event_handler = |event: &TUIEvent, store: &mut Store| match event {
+        TUIEvent::AddSplitPods(env, pods) => {
+            if store.split_env == Some(*env) {
+                store
+                    .split_pods_widget
+                    .as_mut()
+                    .unwrap()
+                    .state_mut::<TableState>()
+                    .set_rows(pods);
+            }
+            None
+        }
+        TUIEvent::Navigate(direction) => {
+            let split_pods_widget = store.split_pods_widget.as_mut().unwrap();
+            if split_pods_widget.get_widget().is_selected {
+                split_pods_widget
+                    .state_mut::<TableState>()
+                    .move_selection(direction);
+            }
+            Some(())
+        }
+        _ => Some(()),
+    };
+    WidgetDescription {
+        widget: split_pods_widget,
+        event_handler: split_pods_event_handler,
+        commands: vec![],
+        keymap: |_, _, _| Some(()),
+    }
+}
+
+pub fn create_namespaces_widget_data() -> WidgetDescription<BodyWidget> {
+    let namespaces_widget = BodyWidget::new(
+        true,
+        Some(selectable_line),
+        None,
+        CliWidget::bordered(
+            CliWidgetId::Namespaces,
+            "Namespace".to_string(),
+            0,
+            CliWidgetData::new(PickerState::default()),
+        ),
+    );
+    let namespaces_event_handler = |event: &TUIEvent, store: &mut Store| match event {
+        TUIEvent::AddNamespaces(namespaces) => {
+            store
+                .namespaces_widget
+                .as_mut()
+                .unwrap()
+                .state_mut::<PickerState>()
+                .options = namespaces
+                .lines()
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty())
+                .collect();
+            refresh_namespace_picker(store);
+            None
+        }
+        TUIEvent::SetNamespaceFilter(filter) => {
+            store
+                .namespaces_widget
+                .as_mut()
+                .unwrap()
+                .state_mut::<PickerState>()
+                .filter = filter.clone();
+            refresh_namespace_picker(store);
+            None
+        }
+        TUIEvent::MoveNamespaceSelection(direction) => {
+            store
+                .namespaces_widget
+                .as_mut()
+                .unwrap()
+                .state_mut::<PickerState>()
+                .table
+                .move_selection(direction);
+            None
+        }
+        _ => Some(()),
+    };
+    WidgetDescription {
+        widget: namespaces_widget,
+        event_handler: namespaces_event_handler,
+        commands: vec![Command::new(
+            "switch-namespace",
+            "Pick another namespace",
+            |_, event_tx| event_tx.send(TUIEvent::OpenNamespacePicker).unwrap(),
+        )],
+        keymap: |keycode: KeyCode, store: &Store, event_tx: &Sender<TUIEvent>| {
+            if !store.namespace_picker_open {
+                return Some(());
+            }
+            let namespaces_widget = store.namespaces_widget.as_ref().unwrap();
+            let filter = &namespaces_widget.state::<PickerState>().filter;
+            let event = match keycode {
+                KeyCode::Esc => TUIEvent::CloseNamespacePicker,
+                KeyCode::Enter => TUIEvent::SelectNamespace,
+                KeyCode::Up => TUIEvent::MoveNamespaceSelection(Direction2::Up),
+                KeyCode::Down => TUIEvent::MoveNamespaceSelection(Direction2::Down),
+                KeyCode::Backspace => {
+                    let mut chars = filter.chars();
+                    chars.next_back();
+                    TUIEvent::SetNamespaceFilter(chars.as_str().to_string())
+                }
+                KeyCode::Char(c) => TUIEvent::SetNamespaceFilter(format!("{}{}", filter, c)),
+                // the picker is modal, other keys do nothing
+                _ => return None,
+            };
+            event_tx.send(event).unwrap();
+            None
+        },
+    }
+}
+
+/// Lists the recent and discovered namespaces matching the typed filter, recent ones first.
+pub fn refresh_namespace_picker(store: &mut Store) {
+    let recent = store
+        .kube_env
+        .map(|env| store.recent_namespaces.for_env(env).to_vec())
+        .unwrap_or_default();
+    let namespaces_widget = store.namespaces_widget.as_mut().unwrap();
+    let picker = namespaces_widget.state_mut::<PickerState>();
+    let filter = picker.filter.clone();
+    let mut candidates = recent.clone();
+    for namespace in &picker.options {
+        if !candidates.contains(namespace) {
+            candidates.push(namespace.clone());
+        }
+    }
+    let rows: Vec<String> = fuzzy_filter(&filter, &candidates)
+        .into_iter()
+        .map(
+            |namespace| match recent.iter().position(|known| known == namespace) {
+                Some(0) => format!("{} (current)", namespace),
+                Some(_) => format!("{} (recent)", namespace),
+                None => namespace.clone(),
+            },
+        )
+        .collect();
+    picker.table.set_rows(&rows.join("\n"));
+    namespaces_widget.get_widget_mut().title = Some(format!(
+        "Namespace (type to filter, enter to pick, esc to cancel): {}",
+        filter
+    ));
+}
+
+pub fn create_palette_widget_data() -> WidgetDescription<BodyWidget> {
+    let palette_widget = BodyWidget::new(
+        true,
+        Some(selectable_line),
+        None,
+        CliWidget::bordered(
+            CliWidgetId::Palette,
+            "Commands".to_string(),
+            0,
+            CliWidgetData::new(PickerState::default()),
+        ),
+    );
+    let palette_event_handler = |event: &TUIEvent, store: &mut Store| match event {
+        TUIEvent::OpenPalette => {
+            store.palette_open = true;
+            *store
+                .palette_widget
+                .as_mut()
+                .unwrap()
+                .state_mut::<PickerState>() = PickerState::default();
+            refresh_palette(store);
+            None
+        }
+        TUIEvent::SetPaletteFilter(filter) => {
+            store
+                .palette_widget
+                .as_mut()
+                .unwrap()
+                .state_mut::<PickerState>()
+                .filter = filter.clone();
+            refresh_palette(store);
+            None
+        }
+        TUIEvent::MovePaletteSelection(direction) => {
+            store
+                .palette_widget
+                .as_mut()
+                .unwrap()
+                .state_mut::<PickerState>()
+                .table
+                .move_selection(direction);
+            None
+        }
+        TUIEvent::ClosePalette => {
+            store.palette_open = false;
+            store.palette_widget.as_mut().unwrap().clear_state();
+            None
+        }
+        _ => Some(()),
+    };
+    WidgetDescription {
+        widget: palette_widget,
+        event_handler: palette_event_handler,
+        commands: vec![],
+        keymap: |keycode: KeyCode, store: &Store, event_tx: &Sender<TUIEvent>| {
+            if !store.palette_open {
+                return Some(());
+            }
+            let palette = store
+                .palette_widget
+                .as_ref()
+                .unwrap()
+                .state::<PickerState>();
+            let filter = &palette.filter;
+            let event = match keycode {
+                KeyCode::Esc => TUIEvent::ClosePalette,
+                KeyCode::Enter => {
+                    event_tx.send(TUIEvent::ClosePalette).unwrap();
+                    let selected = palette.table.selected();
+                    if let Some(command) = store
+                        .commands
+                        .iter()
+                        .find(|command| Some(command.id) == selected)
+                    {
+                        (command.run)(store, event_tx);
+                    }
+                    return None;
+                }
+                KeyCode::Up => TUIEvent::MovePaletteSelection(Direction2::Up),
+                KeyCode::Down => TUIEvent::MovePaletteSelection(Direction2::Down),
+                KeyCode::Backspace => {
+                    let mut chars = filter.chars();
+                    chars.next_back();
+                    TUIEvent::SetPaletteFilter(chars.as_str().to_string())
+                }
+                KeyCode::Char(c) => TUIEvent::SetPaletteFilter(format!("{}{}", filter, c)),
+                _ => return None,
+            };
+            event_tx.send(event).unwrap();
+            None
+        },
+    }
+}
+
+/// Lists the registered commands matching the typed filter, with the key bound to them.
+fn refresh_palette(store: &mut Store) {
+    let options: Vec<String> = store
+        .commands
+        .iter()
+        .map(|command| {
+            format!(
+                "{:<22} {:<50} {}",
+                command.id,
+                command.description,
+                store.keybindings.keys_for(command.id)
+            )
+        })
+        .collect();
+    let palette_widget = store.palette_widget.as_mut().unwrap();
+    let palette = palette_widget.state_mut::<PickerState>();
+    palette.options = options;
+    let filter = palette.filter.clone();
+    let matching: Vec<String> = fuzzy_filter(&filter, &palette.options)
+        .into_iter()
+        .cloned()
+        .collect();
+    palette.table.set_rows(&matching.join("\n"));
+    palette_widget.get_widget_mut().title = Some(format!(
+        "Commands (type to filter, enter to run, esc to cancel): {}",
+        filter
+    ));
+}
+
+/// The row selected in a table widget.
+pub fn selected_row(data: &CliWidgetData) -> Option<String> {
+    data.state
+        .table()
+        .and_then(|table| table.selected())
+        .map(|selected| selected.to_string())
+}
+
+fn selectable_line(data: &CliWidgetData, line: &str) -> Option<Line<'static>> {
+    let selected = selected_row(data);
+    if selected.is_some() && line.split_whitespace().next() == selected.as_deref() {
+        Some(Line::styled(
+            line.to_string(),
+            Style::new().add_modifier(Modifier::REVERSED),
+        ))
+    } else {
+        Some(Line::raw(line.to_string()))
+    }
+}
+
+/// Highlights pods whose memory usage comes close to their limit.
+fn pods_line(data: &CliWidgetData, line: &str) -> Option<Line<'static>> {
+    let mut formatted = selectable_line(data, line)?;
+    if let Some(usage) = line
+        .split_whitespace()
+        .next()
+        .and_then(|pod| data.state::<PodsState>().usage.get(pod))
+    {
+        if memory_pressure(usage) {
+            formatted.patch_style(Style::new().fg(theme().error));
+        }
+    }
+    Some(formatted)
+}
+
+/// Sum of the memory limits of the containers of a pod, `None` when one of them has no limit.
+fn memory_limit(limits: &str, containers: &str) -> Option<u64> {
+    let limits: Vec<&str> = limits
+        .split(',')
+        .filter(|limit| *limit != "<none>")
+        .collect();
+    if limits.len() < containers.split(',').count() {
+        return None;
+    }
+    limits.into_iter().map(parse_memory_mebibytes).sum()
+}
+
+fn memory_pressure(usage: &PodUsage) -> bool {
+    match (usage.limit, usage.memory.last()) {
+        (Some(limit), Some(&memory)) if limit > 0 => {
+            memory * 100 >= limit * config().get_or("top", "memory_warning_percent", 90)
+        }
+        _ => false,
+    }
+}
+
+/// A cpu and a memory column as wide as the kept `kubectl top` history, none when the widget is
+/// too narrow for them.
+fn usage_widths(width: u16) -> Vec<u16> {
+    let history = config().get_or("top", "history", 30) as u16;
+    match history.min(width / 5) {
+        width if width < 3 => vec![],
+        width => vec![width, width],
+    }
+}
+
+/// Draws cpu and memory sparklines from `kubectl top` in the columns next to every pod row.
+fn usage_sparklines(data: &CliWidgetData, f: &mut Frame, columns: &[Rect]) {
+    let [cpu_column, memory_column] = columns else {
+        return;
+    };
+    let width = cpu_column.width;
+    let pods = data.state::<PodsState>();
+    let lines = pods.lines().unwrap_or_default();
+    let scroll = pods.table.scroll_to_selection(cpu_column.height) as usize;
+    let shown = lines.iter().skip(scroll).take(cpu_column.height as usize);
+    for (row, line) in shown.enumerate() {
+        let y = cpu_column.y + row as u16;
+        let cpu_rect = Rect::new(cpu_column.x, y, width, 1);
+        let memory_rect = Rect::new(memory_column.x, y, memory_column.width, 1);
+        let pod = line.split_whitespace().next().unwrap_or_default();
+        if pod == "NAME" {
+            f.render_widget(Paragraph::new("CPU"), cpu_rect);
+            f.render_widget(Paragraph::new("MEM"), memory_rect);
+            continue;
+        }
+        let usage = pods.usage.get(pod).cloned().unwrap_or_default();
+        let (cpu, memory) = (&usage.cpu, &usage.memory);
+        let skip = |samples: &Vec<u64>| samples.len().saturating_sub(width as usize);
+        f.render_widget(
+            Sparkline::default()
+                .data(&cpu[skip(cpu)..])
+                .style(Style::new().fg(theme().accent)),
+            cpu_rect,
+        );
+        let memory_max = usage.limit.filter(|limit| *limit > 0);
+        let mut memory_sparkline =
+            Sparkline::default()
+                .data(&memory[skip(memory)..])
+                .style(Style::new().fg(if memory_pressure(&usage) {
+                    theme().error
+                } else {
+                    theme().ok
+                }));
+        if let Some(limit) = memory_max {
+            memory_sparkline = memory_sparkline.max(limit);
+        }
+        f.render_widget(memory_sparkline, memory_rect);
+    }
+}
+
+/// Parses a kubernetes cpu quantity (`250m`, `1`, `0.5`) into millicores.
+fn parse_cpu_millis(quantity: &str) -> Option<u64> {
+    if let Some(millis) = quantity.strip_suffix('m') {
+        millis.parse().ok()
+    } else if let Some(nanos) = quantity.strip_suffix('n') {
+        nanos.parse::<u64>().ok().map(|nanos| nanos / 1_000_000)
+    } else {
+        quantity
+            .parse::<f64>()
+            .ok()
+            .map(|cores| (cores * 1000.0) as u64)
+    }
+}
+
+/// Parses a kubernetes memory quantity (`128Mi`, `1Gi`, `500M`, bytes) into mebibytes.
+fn parse_memory_mebibytes(quantity: &str) -> Option<u64> {
+    let units: [(&str, f64); 8] = [
+        ("Ki", 1.0 / 1024.0),
+        ("Mi", 1.0),
+        ("Gi", 1024.0),
+        ("Ti", 1024.0 * 1024.0),
+        ("k", 1000.0 / 1048576.0),
+        ("M", 1_000_000.0 / 1048576.0),
+        ("G", 1_000_000_000.0 / 1048576.0),
+        ("T", 1_000_000_000_000.0 / 1048576.0),
+    ];
+    for (suffix, factor) in units {
+        if let Some(value) = quantity.strip_suffix(suffix) {
+            return value
+                .parse::<f64>()
+                .ok()
+                .map(|value| (value * factor) as u64);
+        }
+    }
+    quantity
+        .parse::<f64>()
+        .ok()
+        .map(|bytes| (bytes / 1048576.0) as u64)
+}
+
+/// Formats a `NAME DESIRED READY UPDATED AVAILABLE GENERATION OBSERVED IMAGES` row,
+/// reducing images to their tags and deriving the rollout status from the replica counts.
+fn deployments_line(data: &CliWidgetData, line: &str) -> Option<Line<'static>> {
+    let columns: Vec<&str> = line.split_whitespace().collect();
+    if columns.len() < 8 {
+        return Some(Line::raw(line.to_string()));
+    }
+    if columns[0] == "NAME" {
+        return Some(Line::styled(
+            format!(
+                "{:<30} {:>5} {:>7} {:>9} {:<20} ROLLOUT",
+                "NAME", "READY", "UPDATED", "AVAILABLE", "IMAGES"
+            ),
+            Style::new().add_modifier(Modifier::BOLD),
+        ));
+    }
+    let count = |column: &str| column.parse::<usize>().unwrap_or(0);
+    let (desired, ready, updated, available) = (
+        count(columns[1]),
+        count(columns[2]),
+        count(columns[3]),
+        count(columns[4]),
+    );
+    let (generation, observed) = (count(columns[5]), count(columns[6]));
+    let tags = columns[7]
+        .split(',')
+        .map(|image| match image.rsplit_once(':') {
+            Some((_, tag)) if !tag.contains('/') => tag,
+            _ => "latest",
+        })
+        .collect::<Vec<&str>>()
+        .join(",");
+    let rolling_out =
+        observed < generation || updated < desired || ready < desired || available < desired;
+    let rollout = if rolling_out {
+        let width = 10;
+        let done = match desired {
+            0 => width,
+            _ => updated.min(desired) * width / desired,
+        };
+        format!(
+            "[{}{}] {}/{}",
+            "#".repeat(done),
+            "-".repeat(width - done),
+            updated,
+            desired
+        )
+    } else {
+        "complete".to_string()
+    };
+    let text = format!(
+        "{:<30} {:>5} {:>7} {:>9} {:<20} {}",
+        columns[0],
+        format!("{}/{}", ready, desired),
+        updated,
+        available,
+        tags,
+        rollout
+    );
+    let mut style = Style::new();
+    if rolling_out {
+        style = style.fg(theme().warning);
+    }
+    if selected_row(data).as_deref() == Some(columns[0]) {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
+    Some(Line::styled(text, style))
+}
+
+/// Formats a `LAST TYPE REASON OBJECT MESSAGE` row of the events stream, showing the
+/// age instead of the timestamp and hiding rows for other pods when a filter is set.
+fn events_line(data: &CliWidgetData, line: &str) -> Option<Line<'static>> {
+    let mut columns = line.split_whitespace();
+    let last_seen = columns.next()?;
+    let event_type = columns.next().unwrap_or_default();
+    let reason = columns.next().unwrap_or_default();
+    let object = columns.next().unwrap_or_default();
+    let message = columns.collect::<Vec<&str>>().join(" ");
+    if last_seen == "LAST" {
+        return Some(Line::styled(
+            format!("{:>4} {:<8} {:<20} {:<30} MESSAGE", "AGE", "TYPE", "REASON", "OBJECT"),
+            Style::new().add_modifier(Modifier::BOLD),
+        ));
+    }
+    if let Some(pod) = &data.state::<EventsState>().pod {
+        if pod != object {
+            return None;
+        }
+    }
+    let age = parse_rfc3339(last_seen)
+        .map(|timestamp| human_age(now_secs() - timestamp))
+        .unwrap_or("-".to_string());
+    let text = format!(
+        "{:>4} {:<8} {:<20} {:<30} {}",
+        age, event_type, reason, object, message
+    );
+    if event_type == "Warning" {
+        Some(Line::styled(text, Style::new().fg(theme().warning)))
+    } else {
+        Some(Line::raw(text))
+    }
+}
+
 #[derive(Clone)]
 pub struct WidgetDescription<T: RenderWidget + Clone> {
     widget: T,
     event_handler: fn(&TUIEvent, &mut Store) -> Option<()>,
-    keymap: fn(KeyCode, &Store, &Sender<TUIEvent>),
+    commands: Vec<Command>,
+    keymap: Keymap,
 }
 
 impl<T: RenderWidget + Clone> WidgetDescription<T> {
@@ -546,7 +1942,11 @@ impl<T: RenderWidget + Clone> WidgetDescription<T> {
         self.event_handler
     }
 
-    pub fn get_keymap(&self) -> fn(KeyCode, &Store, &Sender<TUIEvent>) {
+    pub fn get_keymap(&self) -> Keymap {
         self.keymap
     }
+
+    pub fn get_commands(&self) -> Vec<Command> {
+        self.commands.clone()
+    }
 }